@@ -0,0 +1,192 @@
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Serialize,
+};
+
+use crate::error::{Error, ErrorKind, Result};
+
+pub(crate) const CONDITIONAL: &str = "$better_vdf::private::Conditional";
+
+/// A value together with the raw conditional that followed it, such as `$WIN32 && !$X360`
+/// for `"key" "value" [$WIN32 && !$X360]`.
+///
+/// Combine it with [`Deserializer::preserve_conditionals`](crate::Deserializer::preserve_conditionals)
+/// to read every entry without evaluating its condition. Serializing it writes the condition
+/// back, after a string or between the key and the `{` of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conditional<T> {
+    pub value: T,
+    pub condition: Option<String>,
+}
+
+impl<T> Serialize for Conditional<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut conditional = serializer.serialize_struct(CONDITIONAL, 2)?;
+        conditional.serialize_field("condition", &self.condition)?;
+        conditional.serialize_field("value", &self.value)?;
+        conditional.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Conditional<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(CONDITIONAL, ConditionalVisitor(PhantomData))
+    }
+}
+
+struct ConditionalVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for ConditionalVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Conditional<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a conditional value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Conditional {
+            value: T::deserialize(deserializer)?,
+            condition: None,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut value = None;
+        let mut condition = None;
+
+        while let Some(key) = map.next_key::<&str>()? {
+            match key {
+                "value" => value = Some(map.next_value()?),
+                "condition" => condition = Some(map.next_value()?),
+                _ => return Err(de::Error::unknown_field(key, &["value", "condition"])),
+            }
+        }
+
+        Ok(Conditional {
+            value: value.ok_or_else(|| de::Error::missing_field("value"))?,
+            condition,
+        })
+    }
+}
+
+/// Evaluates a conditional expression such as `$WIN32 || ($OSX && !$X360)` against the
+/// defined symbols. Symbols are compared case-insensitively and the `$` is optional.
+pub(crate) fn evaluate(expr: &str, symbols: &[String]) -> Result<bool> {
+    let mut parser = Parser {
+        expr,
+        input: expr,
+        symbols,
+        remaining_depth: 128,
+    };
+
+    let value = parser.or()?;
+    match parser.input.trim_start().is_empty() {
+        true => Ok(value),
+        false => Err(parser.syntax_error()),
+    }
+}
+
+struct Parser<'a> {
+    expr: &'a str,
+    input: &'a str,
+    symbols: &'a [String],
+    remaining_depth: u8,
+}
+
+impl Parser<'_> {
+    fn syntax_error(&self) -> Error {
+        ErrorKind::ConditionSyntax(self.expr.to_owned()).into()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.input = self.input.trim_start();
+        match self.input.strip_prefix(token) {
+            Some(rest) => {
+                self.input = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn or(&mut self) -> Result<bool> {
+        let mut value = self.and()?;
+        while self.eat("||") {
+            value |= self.and()?;
+        }
+        Ok(value)
+    }
+
+    fn and(&mut self) -> Result<bool> {
+        let mut value = self.unary()?;
+        while self.eat("&&") {
+            value &= self.unary()?;
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<bool> {
+        let mut negated = false;
+        while self.eat("!") {
+            negated = !negated;
+        }
+
+        Ok(self.primary()? != negated)
+    }
+
+    fn primary(&mut self) -> Result<bool> {
+        if self.eat("(") {
+            if self.remaining_depth == 0 {
+                return Err(ErrorKind::RecursionLimitExceeded.into());
+            }
+
+            self.remaining_depth -= 1;
+            let value = self.or()?;
+            self.remaining_depth += 1;
+
+            return match self.eat(")") {
+                true => Ok(value),
+                false => Err(self.syntax_error()),
+            };
+        }
+
+        self.eat("$");
+        let len = self
+            .input
+            .find(|x: char| !x.is_alphanumeric() && x != '_')
+            .unwrap_or(self.input.len());
+
+        if len == 0 {
+            return Err(self.syntax_error());
+        }
+
+        let symbol = &self.input[..len];
+        self.input = &self.input[len..];
+
+        Ok(self.symbols.iter().any(|x| x.eq_ignore_ascii_case(symbol)))
+    }
+}
@@ -1,52 +1,237 @@
-use std::str::FromStr;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io,
+    str::FromStr,
+    vec,
+};
 
 use serde::{
-    de::{MapAccess, SeqAccess},
+    de::{
+        value::{BorrowedStrDeserializer, SeqAccessDeserializer, StringDeserializer},
+        DeserializeOwned, EnumAccess, MapAccess, SeqAccess, VariantAccess,
+    },
     Deserialize,
 };
 
-use crate::error::{Error, Result};
+use crate::{
+    condition::{self, CONDITIONAL},
+    error::{Error, ErrorKind, Position, Result},
+    indexed::INDEXED,
+    options::{options, DeserializerOptions},
+    repeated::REPEATED,
+    ser::SeqStyle,
+};
 
 pub struct Deserializer<'de> {
     original: &'de str,
-    input: &'de str,
+    pub(crate) input: &'de str,
+    /// Whether the unbraced root block has not been entered yet.
+    at_root: bool,
+    remaining_depth: u8,
+    pub(crate) options: DeserializerOptions,
+    condition: Option<&'de str>,
+    /// Where the key of the entry being deserialized starts.
+    key: Option<&'de str>,
+    /// The offsets of entries that were already read by a [`crate::repeated`] field.
+    consumed: HashSet<usize>,
+    /// The keys of the blocks scanned along with an enclosing block, by the offset where each
+    /// block's entries start.
+    scans: HashMap<usize, Keys<'de>>,
+}
+
+/// The offsets of the enabled entries of a block, by key.
+type Keys<'de> = HashMap<Cow<'de, str>, Vec<usize>>;
+
+/// How keys are matched against the names serde expects. Case is compared for ASCII letters
+/// only, the way the Source engine compares keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyCase {
+    /// Keys are read exactly as written.
+    #[default]
+    Exact,
+    /// Keys that match the name of a struct field or enum variant regardless of case are read
+    /// as that name. Other keys are read as written.
+    IgnoreCase,
+    /// Every key is read in lowercase, which also suits the keys of maps.
+    Lowercase,
+}
+
+/// What to do with a key that appears more than once in the same block.
+///
+/// Entries read by a [`crate::repeated`] field are not duplicates, although with `LastWins`
+/// such a field only sees the last of them. Keys are compared regardless of case unless
+/// [`KeyCase::Exact`] is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Hand every entry to serde, so that struct fields fail with "duplicate field", maps keep
+    /// the last value and [`Value`](crate::Value) keeps them all.
+    #[default]
+    Keep,
+    /// Fail with [`ErrorKind::DuplicateKey`].
+    Error,
+    /// Keep the first entry and ignore the rest.
+    FirstWins,
+    /// Keep the last entry and ignore the rest.
+    LastWins,
+    /// Present every value of the key as a sequence, which a `Vec<T>` field can collect. Keys
+    /// that appear once are left alone, so use [`crate::repeated`] for fields that may hold a
+    /// single element.
+    Collect,
 }
 
 impl<'de> Deserializer<'de> {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &'de str) -> Self {
+        Self::with_options(input, DeserializerOptions::default())
+    }
+
+    /// Creates a deserializer with the given options, see [`crate::options`].
+    pub fn with_options(input: &'de str, options: DeserializerOptions) -> Self {
         Deserializer {
             original: input,
             input,
+            at_root: true,
+            remaining_depth: 128,
+            options,
+            condition: None,
+            key: None,
+            consumed: HashSet::new(),
+            scans: HashMap::new(),
         }
     }
+
+    /// Enables skipping `/* ... */` block comments, which are not part of the KeyValues format
+    /// but show up in hand-edited files. `//` line comments are always skipped.
+    pub fn block_comments(mut self, enabled: bool) -> Self {
+        self.options.block_comments = enabled;
+        self
+    }
+
+    /// Defines a symbol for conditionals such as `[$WIN32]`. Entries whose condition evaluates
+    /// to false are dropped while deserializing.
+    pub fn define(mut self, symbol: impl Into<String>) -> Self {
+        self.options.symbols.push(symbol.into());
+        self
+    }
+
+    /// Keeps every entry regardless of its condition. The raw condition text can then be read
+    /// through [`Conditional`](crate::Conditional).
+    pub fn preserve_conditionals(mut self, enabled: bool) -> Self {
+        self.options.preserve_conditionals = enabled;
+        self
+    }
+
+    /// Sets how keys that appear more than once in a block are handled.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.options.duplicate_keys = policy;
+        self
+    }
+
+    /// Sets how sequences are read, unless a field overrides it with [`crate::repeated`] or
+    /// [`crate::indexed`].
+    pub fn seq_style(mut self, style: SeqStyle) -> Self {
+        self.options.seq_style = style;
+        self
+    }
+
+    /// Accepts indexed sequences whose keys are not exactly `0, 1, 2, ...`, such as 1-based
+    /// lists or lists with gaps. Elements with numeric keys are read in the order of their keys,
+    /// followed by any other elements in the order they appear.
+    pub fn lenient_indices(mut self, enabled: bool) -> Self {
+        self.options.lenient_indices = enabled;
+        self
+    }
+
+    /// Sets how keys are matched against struct fields and enum variants.
+    pub fn key_case(mut self, key_case: KeyCase) -> Self {
+        self.options.key_case = key_case;
+        self
+    }
+
+    /// Reads an empty string as `None` where an `Option` is expected. Keys that are missing
+    /// are always read as `None`.
+    pub fn empty_as_none(mut self, enabled: bool) -> Self {
+        self.options.empty_as_none = enabled;
+        self
+    }
+
+    /// Creates a deserializer for another document with the same settings.
+    pub(crate) fn with_input<'a>(&self, input: &'a str) -> Deserializer<'a> {
+        Deserializer::with_options(input, self.options.clone())
+    }
 }
 
 pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
     T: Deserialize<'a>,
 {
-    let mut deserializer = Deserializer::from_str(s);
-    let t = T::deserialize(&mut deserializer)?;
-    if deserializer
-        .input
-        .chars()
-        .filter(|&x| x != '\t' && x != '\n' && x != '\r' && x != ' ')
-        .count()
-        == 0
-    {
-        Ok(t)
-    } else {
-        Err(Error::TrailingCharacters)
-    }
+    options().from_str(s)
+}
+
+/// Deserializes UTF-8 bytes, skipping a leading byte order mark.
+///
+/// Since the result may borrow from `v`, UTF-16 input is rejected here. Use [`from_reader`],
+/// which accepts `&[u8]` as well, or decode it with
+/// [`Encoding::decode`](crate::Encoding::decode) first.
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    options().from_slice(v)
+}
+
+/// Deserializes a document read from `reader`, which may be UTF-8 or UTF-16 as detected by
+/// [`Encoding::detect`](crate::Encoding::detect).
+///
+/// The whole input is read before deserializing, so there is no need to wrap `reader` in a
+/// [`BufReader`](std::io::BufReader).
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    options().from_reader(reader)
 }
 
 impl<'de> Deserializer<'de> {
+    /// The position of the next token.
+    pub fn position(&self) -> Position {
+        self.position_of(self.trim_start(self.input))
+    }
+
+    /// The position of `at`, which must be a slice of the input.
+    fn position_of(&self, at: &str) -> Position {
+        Position::new(self.original, self.offset_of(at))
+    }
+
+    /// The byte offset of `at`, which must be a slice of the input.
+    fn offset_of(&self, at: &str) -> usize {
+        at.as_ptr() as usize - self.original.as_ptr() as usize
+    }
+
+    /// Creates an error positioned at the next token.
+    pub(crate) fn error(&self, kind: ErrorKind) -> Error {
+        self.error_at(self.trim_start(self.input), kind)
+    }
+
+    /// Creates an error positioned at `at`, which must be a slice of the input.
+    fn error_at(&self, at: &str, kind: ErrorKind) -> Error {
+        Error::from(kind).or_position(|| self.position_of(at))
+    }
+
+    fn eof(&self) -> Error {
+        self.error_at(&self.original[self.original.len()..], ErrorKind::Eof)
+    }
+
+    /// Attaches the position of the next token to errors raised without one, such as those
+    /// raised by visitors through `de::Error::custom`.
+    pub(crate) fn fix_position(&self, err: Error) -> Error {
+        err.or_position(|| self.position())
+    }
+
     fn peek_char(&self) -> Result<char> {
-        self.input
-            .chars()
-            .next()
-            .ok_or(Error::Eof)
-            .or_else(|x| panic!("{x:?}"))
+        self.input.chars().next().ok_or_else(|| self.eof())
     }
 
     fn next_char(&mut self) -> Result<char> {
@@ -55,69 +240,522 @@ impl<'de> Deserializer<'de> {
         Ok(ch)
     }
 
-    fn peek_real_char(&self) -> Result<char> {
-        self.input
-            .chars()
-            .find(|&x| x != '\t' && x != '\n' && x != '\r' && x != ' ')
-            .ok_or(Error::Eof)
-        // .or_else(|x| panic!("{x:?}"))
+    pub(crate) fn peek_real_char(&self) -> Result<char> {
+        let input = self.trim_start(self.input);
+        if self.options.block_comments && input.starts_with("/*") {
+            return Err(self.error_at(input, ErrorKind::UnterminatedComment));
+        }
+
+        input.chars().next().ok_or_else(|| self.eof())
     }
 
-    fn next_real_char(&mut self) -> Result<char> {
-        let mut ch = self.next_char()?;
+    pub(crate) fn next_real_char(&mut self) -> Result<char> {
+        self.skip_whitespace();
+        self.next_char()
+    }
 
-        while ch == '\t' || ch == '\n' || ch == '\r' || ch == ' ' {
-            ch = self.next_char()?;
-        }
+    /// Skips whitespace and comments.
+    pub(crate) fn skip_whitespace(&mut self) {
+        self.input = self.trim_start(self.input);
+    }
 
-        Ok(ch)
+    fn trim_start(&self, mut input: &'de str) -> &'de str {
+        loop {
+            input = input.trim_start_matches(|x| self.options.whitespace.contains(x));
+
+            if let Some(rest) = input.strip_prefix("//") {
+                input = rest.find('\n').map_or("", |len| &rest[len..]);
+            } else if let Some(rest) = input
+                .strip_prefix("/*")
+                .filter(|_| self.options.block_comments)
+            {
+                // An unterminated comment is left for `peek_real_char` to report.
+                match rest.find("*/") {
+                    Some(len) => input = &rest[len + 2..],
+                    None => return input,
+                }
+            } else {
+                return input;
+            }
+        }
     }
 
-    fn parse_string(&mut self) -> Result<&'de str> {
-        if self.next_real_char()? != '"' {
-            return Err(Error::ExpectedString);
+    /// Parses a string token, which is either quoted or a bare word such as `LayoutVersion`.
+    pub(crate) fn parse_string(&mut self) -> Result<Cow<'de, str>> {
+        match self.peek_real_char()? {
+            '"' => {
+                self.next_real_char()?;
+                self.parse_quoted()
+            }
+            ch @ ('{' | '}' | '[') => Err(self.error(ErrorKind::ExpectedString(ch))),
+            _ => Ok(Cow::Borrowed(self.parse_unquoted())),
         }
-        match self.input.find('"') {
-            Some(len) => {
+    }
+
+    /// Parses an unquoted token, which ends at whitespace, braces, quotes or the `[` of a
+    /// conditional.
+    fn parse_unquoted(&mut self) -> &'de str {
+        self.skip_whitespace();
+        let len = self
+            .input
+            .find(|x| self.options.whitespace.contains(x) || matches!(x, '{' | '}' | '"' | '['))
+            .unwrap_or(self.input.len());
+        let s = &self.input[..len];
+        self.input = &self.input[len..];
+        s
+    }
+
+    /// Parses the rest of a quoted string, resolving the `\"`, `\\`, `\n` and `\t` escape
+    /// sequences.
+    ///
+    /// The result borrows from the input unless an escape sequence had to be resolved.
+    fn parse_quoted(&mut self) -> Result<Cow<'de, str>> {
+        match self.input.find(['"', '\\']) {
+            Some(len) if self.input.as_bytes()[len] == b'"' => {
                 let s = &self.input[..len];
                 self.input = &self.input[len + 1..];
-                Ok(s)
+                Ok(Cow::Borrowed(s))
             }
-            None => {
-                // Err(Error::Eof)
-                panic!("{:?}", Error::Eof)
+            Some(len) => {
+                let mut s = String::from(&self.input[..len]);
+                self.input = &self.input[len..];
+
+                loop {
+                    match self.next_char()? {
+                        '"' => break,
+                        '\\' => match self.next_char()? {
+                            'n' => s.push('\n'),
+                            't' => s.push('\t'),
+                            '\\' => s.push('\\'),
+                            '"' => s.push('"'),
+                            ch => {
+                                s.push('\\');
+                                s.push(ch);
+                            }
+                        },
+                        ch => s.push(ch),
+                    }
+                }
+
+                Ok(Cow::Owned(s))
             }
+            None => Err(self.eof()),
         }
     }
 
     fn parse_bool(&mut self) -> Result<bool> {
+        let start = self.trim_start(self.input);
         let str = self.parse_string()?;
 
-        match str {
-            "1" => Ok(true),
-            "0" => Ok(false),
-            _ => Err(Error::ExpectedBoolean),
-        }
+        self.options
+            .bools
+            .parse(&str)
+            .ok_or_else(|| self.error_at(start, ErrorKind::ExpectedBoolean(str.into_owned())))
     }
 
     fn parse_value<T>(&mut self) -> Result<T>
     where
         T: FromStr,
     {
+        let start = self.trim_start(self.input);
+        let str = self.parse_string()?;
+
+        str.parse::<T>()
+            .map_err(|_| self.error_at(start, ErrorKind::ExpectedInteger(str.into_owned())))
+    }
+
+    /// Parses a float, also accepting the `1.#INF`, `1.#QNAN` and `-1.#IND` spellings of the
+    /// MSVC runtime that older Source tools were built with.
+    fn parse_float<T>(&mut self) -> Result<T>
+    where
+        T: FromStr,
+    {
+        let start = self.trim_start(self.input);
         let str = self.parse_string()?;
 
-        str.parse::<T>().map_err(|_| Error::ExpectedInteger)
+        let (sign, unsigned) = match str.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", str.strip_prefix('+').unwrap_or(&str)),
+        };
+        let normalized = match unsigned {
+            x if x.starts_with("1.#INF") => Cow::Owned(format!("{sign}inf")),
+            x if ["1.#QNAN", "1.#SNAN", "1.#IND"]
+                .iter()
+                .any(|nan| x.starts_with(nan)) =>
+            {
+                Cow::Borrowed("NaN")
+            }
+            _ => Cow::Borrowed(&*str),
+        };
+
+        normalized
+            .parse::<T>()
+            .map_err(|_| self.error_at(start, ErrorKind::ExpectedFloat(str.to_string())))
+    }
+
+    /// Parses a conditional such as `[$WIN32]`, returning the text between the brackets.
+    pub(crate) fn parse_condition(&mut self) -> Result<Option<&'de str>> {
+        if self.peek_real_char().ok() != Some('[') {
+            return Ok(None);
+        }
+
+        self.next_real_char()?;
+        match self.input.find(']') {
+            Some(len) => {
+                let s = &self.input[..len];
+                self.input = &self.input[len + 1..];
+                Ok(Some(s.trim()))
+            }
+            None => Err(self.eof()),
+        }
+    }
+
+    /// Consumes the `{` that opens a block, or fails with `expected` and the character found.
+    pub(crate) fn open_block(&mut self, expected: fn(char) -> ErrorKind) -> Result<()> {
+        match self.peek_real_char()? {
+            '{' if self.remaining_depth == 0 => Err(self.error(ErrorKind::RecursionLimitExceeded)),
+            '{' => {
+                self.remaining_depth -= 1;
+                self.next_real_char()?;
+                Ok(())
+            }
+            ch => Err(self.error(expected(ch))),
+        }
+    }
+
+    /// Consumes the `}` that closes a block, or fails with `expected` and the character found.
+    pub(crate) fn close_block(&mut self, expected: fn(char) -> ErrorKind) -> Result<()> {
+        match self.peek_real_char()? {
+            '}' => {
+                self.remaining_depth += 1;
+                self.next_real_char()?;
+                Ok(())
+            }
+            ch => Err(self.error(expected(ch))),
+        }
+    }
+
+    /// Skips over a string or a `{ ... }` block, including any conditionals inside of it.
+    fn skip_value(&mut self) -> Result<()> {
+        if self.peek_real_char()? != '{' {
+            self.parse_string()?;
+            return Ok(());
+        }
+
+        self.open_block(ErrorKind::ExpectedMap)?;
+        while self.peek_real_char()? != '}' {
+            self.skip_entry()?;
+        }
+        self.close_block(ErrorKind::ExpectedMapEnd)
+    }
+
+    /// Skips over a key, its value and their conditionals.
+    fn skip_entry(&mut self) -> Result<()> {
+        self.parse_string()?;
+        self.parse_condition()?;
+        self.skip_value()?;
+        self.parse_condition()?;
+
+        Ok(())
+    }
+
+    /// Drops the entries whose condition evaluates to false, leaving the input at the next
+    /// entry to deserialize and its condition in `self.condition`.
+    pub(crate) fn skip_disabled_entries(&mut self) -> Result<()> {
+        loop {
+            if matches!(self.peek_real_char(), Ok('}') | Err(_)) {
+                return Ok(());
+            }
+
+            let start = self.input;
+            self.parse_string()?;
+
+            let mut condition = self.parse_condition()?;
+            let block = self.peek_real_char()? == '{';
+            if !block {
+                self.parse_string()?;
+                if let Some(trailing) = self.parse_condition()? {
+                    condition = Some(trailing);
+                }
+            }
+
+            let enabled = match condition {
+                Some(expr) if !self.options.preserve_conditionals => {
+                    condition::evaluate(expr, &self.options.symbols)
+                        .map_err(|err| err.or_position(|| self.position_of(expr)))?
+                }
+                _ => true,
+            };
+
+            if enabled {
+                self.input = start;
+                self.condition = condition;
+                return Ok(());
+            }
+
+            if block {
+                self.skip_value()?;
+                self.parse_condition()?;
+            }
+        }
     }
 }
 
-impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de> Deserializer<'de> {
+    /// Lists the offsets of the enabled entries of the current block by key, without
+    /// consuming any input.
+    ///
+    /// Nested blocks are scanned along the way and kept until they are read, so that each block
+    /// of the document is only scanned once.
+    fn scan_keys(&mut self) -> Result<Keys<'de>> {
+        if let Some(keys) = self.scans.remove(&self.offset_of(self.input)) {
+            return Ok(keys);
+        }
+
+        let start = self.input;
+        let keys = self.scan_block()?;
+
+        self.input = start;
+        self.condition = None;
+        Ok(keys)
+    }
+
+    fn scan_block(&mut self) -> Result<Keys<'de>> {
+        let mut keys = Keys::new();
+
+        loop {
+            self.skip_disabled_entries()?;
+            if matches!(self.peek_real_char(), Ok('}') | Err(_)) {
+                return Ok(keys);
+            }
+
+            let offset = self.offset_of(self.trim_start(self.input));
+            let key = self.parse_string()?;
+            keys.entry(self.fold_key(key)).or_default().push(offset);
+            self.parse_condition()?;
+
+            if self.peek_real_char()? == '{' {
+                self.open_block(ErrorKind::ExpectedMap)?;
+                let nested = self.offset_of(self.input);
+                let nested_keys = self.scan_block()?;
+                self.scans.insert(nested, nested_keys);
+                self.close_block(ErrorKind::ExpectedMapEnd)?;
+            } else {
+                self.parse_string()?;
+            }
+            self.parse_condition()?;
+        }
+    }
+
+    /// Deserializes the value of the current entry along with the values of the later entries
+    /// of the block that share its key, which are then skipped by the block.
+    fn deserialize_repeated<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let Some(key_start) = self.key.take() else {
+            return serde::de::Deserializer::deserialize_seq(self, visitor);
+        };
+
+        let value_start = self.input;
+        self.input = key_start;
+        let key = self.parse_string()?;
+        let mut offsets = vec![self.offset_of(self.trim_start(key_start))];
+
+        self.parse_condition()?;
+        self.skip_value()?;
+        self.parse_condition()?;
+
+        loop {
+            self.skip_disabled_entries()?;
+            if matches!(self.peek_real_char(), Ok('}') | Err(_)) {
+                break;
+            }
+
+            let offset = self.offset_of(self.trim_start(self.input));
+            let other = self.parse_string()?;
+            if self.fold_key(other) == self.fold_key(key.clone()) {
+                offsets.push(offset);
+            }
+            self.parse_condition()?;
+            self.skip_value()?;
+            self.parse_condition()?;
+        }
+
+        self.consumed.extend(&offsets[1..]);
+        self.input = value_start;
+
+        let first = offsets[0];
+        let value = visitor.visit_seq(Occurrences {
+            de: self,
+            offsets: offsets.into_iter(),
+        })?;
+        self.resume_after(first)?;
+
+        Ok(value)
+    }
+
+    /// Deserializes an indexed sequence regardless of its keys, sorting the numeric ones.
+    fn deserialize_lenient_seq<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.open_block(ErrorKind::ExpectedArray)?;
+
+        let mut entries = Vec::new();
+        loop {
+            self.skip_disabled_entries()?;
+            if self.peek_real_char()? == '}' {
+                break;
+            }
+
+            let offset = self.offset_of(self.trim_start(self.input));
+            let index = self.parse_string()?.parse::<u64>().ok();
+            entries.push((index, offset));
+            self.parse_condition()?;
+            self.skip_value()?;
+            self.parse_condition()?;
+        }
+
+        let end = self.input;
+        entries.sort_by_key(|&(index, _)| (index.is_none(), index));
+
+        let value = visitor.visit_seq(Occurrences {
+            de: self,
+            offsets: entries
+                .into_iter()
+                .map(|(_, offset)| offset)
+                .collect::<Vec<_>>()
+                .into_iter(),
+        })?;
+
+        self.input = end;
+        self.condition = None;
+        self.close_block(ErrorKind::ExpectedArrayEnd)?;
+
+        Ok(value)
+    }
+
+    /// The form of `key` that is compared when looking for duplicates.
+    fn fold_key(&self, key: Cow<'de, str>) -> Cow<'de, str> {
+        match self.options.key_case {
+            KeyCase::Exact => key,
+            _ if !key.bytes().any(|x| x.is_ascii_uppercase()) => key,
+            _ => Cow::Owned(key.to_ascii_lowercase()),
+        }
+    }
+
+    /// The name that `key` is read as, if it differs from the key as written.
+    fn rename_key(&self, key: &str, names: &'static [&'static str]) -> Option<Cow<'de, str>> {
+        match self.options.key_case {
+            KeyCase::Exact => None,
+            KeyCase::IgnoreCase => names
+                .iter()
+                .find(|x| **x != key && x.eq_ignore_ascii_case(key))
+                .map(|x| Cow::Borrowed(*x)),
+            KeyCase::Lowercase => key
+                .bytes()
+                .any(|x| x.is_ascii_uppercase())
+                .then(|| Cow::Owned(key.to_ascii_lowercase())),
+        }
+    }
+
+    /// Deserializes a key, which may be renamed to one of `names` depending on the key case.
+    fn deserialize_key<T>(&mut self, seed: T, names: &'static [&'static str]) -> Result<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        if self.options.key_case != KeyCase::Exact {
+            let start = self.input;
+            let key = self.parse_string()?;
+
+            match self.rename_key(&key, names) {
+                Some(Cow::Borrowed(name)) => {
+                    return seed.deserialize(BorrowedStrDeserializer::new(name))
+                }
+                Some(Cow::Owned(name)) => return seed.deserialize(StringDeserializer::new(name)),
+                None => self.input = start,
+            }
+        }
+
+        seed.deserialize(self)
+    }
+
+    /// Deserializes a block, or the unbraced root, as a map with the given field names.
+    fn deserialize_block<V>(
+        &mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let begin = std::mem::replace(&mut self.at_root, false)
+            && !(self.options.braced_root && self.peek_real_char().ok() == Some('{'));
+
+        if begin {
+            return visitor.visit_map(VdfMap::new(self, begin, fields));
+        }
+
+        self.open_block(ErrorKind::ExpectedMap)?;
+        let value = visitor.visit_map(VdfMap::new(self, begin, fields))?;
+        self.close_block(ErrorKind::ExpectedMapEnd)?;
+
+        Ok(value)
+    }
+
+    /// Whether the rest of the input is a single string, which is how a unit variant is
+    /// written at the root.
+    fn is_lone_string(&mut self) -> bool {
+        let start = self.input;
+        let lone = self.parse_string().is_ok()
+            && self.parse_condition().is_ok()
+            && matches!(self.peek_real_char(), Err(err) if matches!(err.kind(), ErrorKind::Eof));
+
+        self.input = start;
+        lone
+    }
+
+    /// Skips the entries that a repeated sequence already read, up to the next one it did not.
+    fn skip_consumed_entries(&mut self) -> Result<()> {
+        loop {
+            self.skip_disabled_entries()?;
+            let offset = self.offset_of(self.trim_start(self.input));
+            if !self.consumed.remove(&offset) {
+                return Ok(());
+            }
+            self.skip_entry()?;
+        }
+    }
+
+    /// Moves the input past the entry at `offset`, once its occurrences have been read.
+    fn resume_after(&mut self, offset: usize) -> Result<()> {
+        self.input = &self.original[offset..];
+        self.skip_entry()?;
+        self.condition = None;
+        Ok(())
+    }
+}
+
+impl<'de> serde::de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
-    fn deserialize_any<V>(self, _visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
+    /// Every VDF node is either a string or a block. Blocks (including the unbraced root) are
+    /// visited as maps and strings as strings, even when they look like numbers, so that
+    /// `#[serde(flatten)]` maps of strings keep values such as `"440"`. Numeric fields that serde
+    /// buffers, such as those of internally tagged enums, can use [`crate::number`].
+    fn deserialize_any<V>(self, visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::NonSelfDescribing)
+        if self.at_root || self.peek_real_char()? == '{' {
+            return self.deserialize_map(visitor);
+        }
+
+        match self.parse_string()? {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
@@ -187,28 +825,31 @@ impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_f32(self.parse_value()?)
+        visitor.visit_f32(self.parse_float()?)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_f32(self.parse_value()?)
+        visitor.visit_f64(self.parse_float()?)
     }
 
     fn deserialize_char<V>(self, _visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::UnsupportedType)
+        Err(self.error(ErrorKind::UnsupportedType))
     }
 
     fn deserialize_str<V>(self, visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_borrowed_str(self.parse_string()?)
+        match self.parse_string()? {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s) => visitor.visit_string(s),
+        }
     }
 
     fn deserialize_string<V>(self, visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
@@ -222,20 +863,28 @@ impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::UnsupportedType)
+        Err(self.error(ErrorKind::UnsupportedType))
     }
 
     fn deserialize_byte_buf<V>(self, _visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::UnsupportedType)
+        Err(self.error(ErrorKind::UnsupportedType))
     }
 
     fn deserialize_option<V>(self, visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
+        if self.options.empty_as_none && !self.at_root && self.peek_real_char()? != '{' {
+            let start = self.input;
+            if self.parse_string()?.is_empty() {
+                return visitor.visit_none();
+            }
+            self.input = start;
+        }
+
         visitor.visit_some(self)
     }
 
@@ -243,7 +892,7 @@ impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::UnsupportedType)
+        Err(self.error(ErrorKind::UnsupportedType))
     }
 
     fn deserialize_unit_struct<V>(
@@ -254,35 +903,51 @@ impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::UnsupportedType)
+        Err(self.error(ErrorKind::UnsupportedType))
     }
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name == CONDITIONAL {
+            let condition = self.condition.take();
+            visitor.visit_map(ConditionalAccess {
+                de: self,
+                condition,
+                state: 0,
+            })
+        } else if name == REPEATED {
+            self.deserialize_repeated(visitor)
+        } else if name == INDEXED {
+            self.key = None;
+            self.deserialize_seq(visitor)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        if self.next_real_char()? == '{' {
-            let value = visitor.visit_seq(VdfSeq::new(self))?;
+        if self.options.seq_style == SeqStyle::Repeated && self.key.is_some() {
+            return self.deserialize_repeated(visitor);
+        }
 
-            if self.next_real_char()? == '}' {
-                Ok(value)
-            } else {
-                Err(Error::ExpectedArrayEnd)
-            }
-        } else {
-            Err(Error::ExpectedArray)
+        if self.options.lenient_indices {
+            return self.deserialize_lenient_seq(visitor);
         }
+
+        self.open_block(ErrorKind::ExpectedArray)?;
+        let value = visitor.visit_seq(VdfSeq::new(self))?;
+        self.close_block(ErrorKind::ExpectedArrayEnd)?;
+
+        Ok(value)
     }
 
     fn deserialize_tuple<V>(
@@ -312,43 +977,59 @@ impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        let begin = self.original.as_ptr() == self.input.as_ptr();
-
-        if begin || self.next_real_char()? == '{' {
-            let value = visitor.visit_map(VdfMap::new(self))?;
-
-            if begin || self.next_real_char()? == '}' {
-                Ok(value)
-            } else {
-                Err(Error::ExpectedMapEnd)
-            }
-        } else {
-            Err(Error::ExpectedMap)
-        }
+        self.deserialize_block(&[], visitor)
     }
 
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        self.deserialize_block(fields, visitor)
     }
 
+    /// Unit variants are plain strings, while the other variants are a block holding a single
+    /// entry, keyed by the name of the variant.
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
-        _visitor: V,
+        variants: &'static [&'static str],
+        visitor: V,
     ) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(Error::UnsupportedType)
+        self.key = None;
+
+        let at_root = std::mem::replace(&mut self.at_root, false);
+        let value = if at_root && !self.is_lone_string() {
+            visitor.visit_enum(VdfEnum {
+                de: self,
+                variants,
+                key: None,
+            })?
+        } else if self.peek_real_char()? == '{' {
+            self.open_block(ErrorKind::ExpectedMap)?;
+            let value = visitor.visit_enum(VdfEnum {
+                de: self,
+                variants,
+                key: None,
+            })?;
+            self.close_block(ErrorKind::ExpectedMapEnd)?;
+            value
+        } else {
+            let variant = self.parse_string()?;
+            return match self.rename_key(&variant, variants).unwrap_or(variant) {
+                Cow::Borrowed(s) => visitor.visit_enum(BorrowedStrDeserializer::new(s)),
+                Cow::Owned(s) => visitor.visit_enum(StringDeserializer::new(s)),
+            };
+        };
+
+        Ok(value)
     }
 
     fn deserialize_identifier<V>(
@@ -368,7 +1049,15 @@ impl<'de, 'a> serde::de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: serde::de::Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        if std::mem::replace(&mut self.at_root, false) && self.peek_real_char().ok() != Some('{') {
+            while self.peek_real_char().is_ok() {
+                self.skip_entry()?;
+            }
+        } else {
+            self.skip_value()?;
+        }
+
+        visitor.visit_unit()
     }
 }
 
@@ -393,22 +1082,37 @@ impl<'de, 'a> SeqAccess<'de> for VdfSeq<'a, 'de> {
     where
         T: serde::de::DeserializeSeed<'de>,
     {
+        self.de.skip_disabled_entries()?;
+
         match self.de.peek_real_char()? {
             '}' => Ok(None),
-            '"' => {
-                //
+            '{' => Err(self.de.error(ErrorKind::SeqSyntax('{'))),
+            _ => {
+                let start = self.de.trim_start(self.de.input);
                 let ind: usize = self.de.parse_value()?;
 
                 if ind != self.index {
-                    println!("Expected index {}, found {ind}", self.index);
-                    return Err(Error::ArrayIndex);
+                    return Err(self.de.error_at(
+                        start,
+                        ErrorKind::ArrayIndex {
+                            expected: self.index,
+                            found: ind,
+                        },
+                    ));
                 }
 
                 self.index += 1;
 
-                seed.deserialize(&mut *self.de).map(Some)
+                self.de.key = None;
+                self.de.parse_condition()?;
+                let value = seed
+                    .deserialize(&mut *self.de)
+                    .map_err(|err| self.de.fix_position(err))?;
+                self.de.parse_condition()?;
+                self.de.condition = None;
+
+                Ok(Some(value))
             }
-            _ => Err(Error::SeqSyntax),
         }
     }
 }
@@ -416,15 +1120,48 @@ impl<'de, 'a> SeqAccess<'de> for VdfSeq<'a, 'de> {
 struct VdfMap<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
     root: bool,
-    initialized: bool,
+    /// The names of the struct fields, if this block is a struct.
+    fields: &'static [&'static str],
+    /// The offsets of the entries of each key, unless duplicates are passed on as they are.
+    keys: Option<Keys<'de>>,
+    /// The offsets of every entry of the current key, if they are collected into a sequence.
+    collected: Option<Vec<usize>>,
 }
 
 impl<'a, 'de> VdfMap<'a, 'de> {
-    fn new(de: &'a mut Deserializer<'de>) -> Self {
+    fn new(de: &'a mut Deserializer<'de>, root: bool, fields: &'static [&'static str]) -> Self {
         VdfMap {
-            root: de.input.as_ptr() == de.original.as_ptr(),
+            root,
             de,
-            initialized: false,
+            fields,
+            keys: None,
+            collected: None,
+        }
+    }
+
+    /// Applies the duplicate key policy to the entry at `offset`, returning whether it should
+    /// be deserialized.
+    fn accept(&mut self, key: Cow<'de, str>, offset: usize) -> Result<bool> {
+        let folded = self.de.fold_key(key.clone());
+        let Some(offsets) = self.keys.as_ref().and_then(|x| x.get(&folded)) else {
+            return Ok(true);
+        };
+
+        match self.de.options.duplicate_keys {
+            DuplicateKeys::Keep => Ok(true),
+            DuplicateKeys::Error if offsets[0] != offset => Err(self.de.error_at(
+                &self.de.original[offset..],
+                ErrorKind::DuplicateKey(key.into_owned()),
+            )),
+            DuplicateKeys::Error => Ok(true),
+            DuplicateKeys::FirstWins => Ok(offsets[0] == offset),
+            DuplicateKeys::LastWins => Ok(offsets.last() == Some(&offset)),
+            DuplicateKeys::Collect => {
+                if offsets.len() > 1 && offsets[0] == offset {
+                    self.collected = Some(offsets.clone());
+                }
+                Ok(offsets[0] == offset)
+            }
         }
     }
 }
@@ -439,21 +1176,220 @@ impl<'de, 'a> MapAccess<'de> for VdfMap<'a, 'de> {
     where
         K: serde::de::DeserializeSeed<'de>,
     {
-        let res = self.de.peek_real_char();
+        if self.keys.is_none() && self.de.options.duplicate_keys != DuplicateKeys::Keep {
+            self.keys = Some(self.de.scan_keys()?);
+        }
 
-        if self.root && self.initialized && res.is_err() || res? == '}' {
-            return Ok(None);
+        loop {
+            self.de.skip_disabled_entries()?;
+
+            // The root has no braces, so it ends with the input, and may be empty.
+            let res = self.de.peek_real_char();
+            let eof = matches!(&res, Err(err) if matches!(err.kind(), ErrorKind::Eof));
+
+            if self.root && eof || res? == '}' {
+                return Ok(None);
+            }
+
+            let start = self.de.trim_start(self.de.input);
+            let offset = self.de.offset_of(start);
+
+            if self.de.consumed.remove(&offset) {
+                self.de.skip_entry()?;
+                continue;
+            }
+
+            if self.keys.is_some() {
+                let key = self.de.parse_string()?;
+                self.de.input = start;
+
+                if !self.accept(key, offset)? {
+                    self.de.skip_entry()?;
+                    continue;
+                }
+            }
+
+            self.de.key = Some(start);
+
+            return self
+                .de
+                .deserialize_key(seed, self.fields)
+                .map(Some)
+                .map_err(|err| self.de.fix_position(err));
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> std::prelude::v1::Result<V::Value, Self::Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        if let Some(offsets) = self.collected.take() {
+            let first = offsets[0];
+            let value = seed.deserialize(SeqAccessDeserializer::new(Occurrences {
+                de: &mut *self.de,
+                offsets: offsets.into_iter(),
+            }))?;
+            self.de.resume_after(first)?;
+
+            return Ok(value);
         }
 
-        self.initialized = true;
+        self.de.parse_condition()?;
+        let value = seed
+            .deserialize(&mut *self.de)
+            .map_err(|err| self.de.fix_position(err))?;
+        self.de.parse_condition()?;
+        self.de.condition = None;
+
+        Ok(value)
+    }
+}
+
+/// Presents the values of the entries at `offsets` as a sequence.
+struct Occurrences<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    offsets: vec::IntoIter<usize>,
+}
+
+impl<'de> SeqAccess<'de> for Occurrences<'_, 'de> {
+    type Error = Error;
 
-        seed.deserialize(&mut *self.de).map(Some)
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> std::prelude::v1::Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        let Some(offset) = self.offsets.next() else {
+            return Ok(None);
+        };
+
+        self.de.input = &self.de.original[offset..];
+        self.de.parse_string()?;
+        self.de.parse_condition()?;
+        self.de.key = None;
+        let value = seed
+            .deserialize(&mut *self.de)
+            .map_err(|err| self.de.fix_position(err))?;
+        self.de.parse_condition()?;
+        self.de.condition = None;
+
+        Ok(Some(value))
+    }
+}
+
+/// Presents the `"Variant" value` entry of a block as an enum.
+struct VdfEnum<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    variants: &'static [&'static str],
+    /// Where the entry of the variant starts, once its name has been read.
+    key: Option<&'de str>,
+}
+
+impl<'de> EnumAccess<'de> for VdfEnum<'_, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> std::prelude::v1::Result<(V::Value, Self), Self::Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        self.de.skip_disabled_entries()?;
+        let key = self.de.trim_start(self.de.input);
+        let variant = self.de.deserialize_key(seed, self.variants)?;
+        self.de.parse_condition()?;
+
+        Ok((
+            variant,
+            VdfEnum {
+                key: Some(key),
+                ..self
+            },
+        ))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VdfEnum<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        self.de.skip_value()?;
+        self.de.parse_condition()?;
+        Ok(())
+    }
+
+    /// The variant is the key of its value, so a sequence written as repeated keys is read
+    /// back from every entry named after the variant.
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        self.de.key = self.key;
+        let value = seed.deserialize(&mut *self.de)?;
+        self.de.key = None;
+        self.de.parse_condition()?;
+        self.de.skip_consumed_entries()?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let value = serde::de::Deserializer::deserialize_seq(&mut *self.de, visitor)?;
+        self.de.parse_condition()?;
+        Ok(value)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let value = self.de.deserialize_block(fields, visitor)?;
+        self.de.parse_condition()?;
+        Ok(value)
+    }
+}
+
+/// Presents a value and the condition that followed it as a `{ value, condition }` map.
+struct ConditionalAccess<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    condition: Option<&'de str>,
+    state: u8,
+}
+
+impl<'de> MapAccess<'de> for ConditionalAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> std::prelude::v1::Result<Option<K::Value>, Self::Error>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        let key = match self.state {
+            0 => "value",
+            1 if self.condition.is_some() => "condition",
+            _ => return Ok(None),
+        };
+
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> std::prelude::v1::Result<V::Value, Self::Error>
     where
         V: serde::de::DeserializeSeed<'de>,
     {
-        seed.deserialize(&mut *self.de)
+        self.state += 1;
+
+        match self.state {
+            1 => seed.deserialize(&mut *self.de),
+            _ => seed.deserialize(BorrowedStrDeserializer::new(
+                self.condition.unwrap_or_default(),
+            )),
+        }
     }
 }
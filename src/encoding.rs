@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+
+use crate::error::{Error, ErrorKind, Position, Result};
+
+/// The text encodings VDF files are found in. Steam writes UTF-8, while the localization files
+/// in `resource/` are UTF-16LE with a byte order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// UTF-8 with a byte order mark.
+    Utf8Bom,
+    /// UTF-16LE with a byte order mark.
+    Utf16Le,
+    /// UTF-16BE with a byte order mark.
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Detects the encoding of `bytes` from its byte order mark, defaulting to UTF-8.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => Encoding::Utf8Bom,
+            [0xFF, 0xFE, ..] => Encoding::Utf16Le,
+            [0xFE, 0xFF, ..] => Encoding::Utf16Be,
+            _ => Encoding::Utf8,
+        }
+    }
+
+    /// The byte order mark that starts text in this encoding.
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => b"",
+            Encoding::Utf8Bom => b"\xEF\xBB\xBF",
+            Encoding::Utf16Le => b"\xFF\xFE",
+            Encoding::Utf16Be => b"\xFE\xFF",
+        }
+    }
+
+    /// Decodes `bytes`, skipping the byte order mark if present. UTF-8 input is borrowed.
+    pub fn decode(self, bytes: &[u8]) -> Result<Cow<'_, str>> {
+        let bytes = bytes.strip_prefix(self.bom()).unwrap_or(bytes);
+
+        match self {
+            Encoding::Utf8 | Encoding::Utf8Bom => decode_utf8(bytes).map(Cow::Borrowed),
+            Encoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes).map(Cow::Owned),
+            Encoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes).map(Cow::Owned),
+        }
+    }
+
+    /// Encodes `s`, starting with the byte order mark of this encoding.
+    pub fn encode(self, s: &str) -> Vec<u8> {
+        let mut bytes = self.bom().to_vec();
+
+        match self {
+            Encoding::Utf8 | Encoding::Utf8Bom => bytes.extend_from_slice(s.as_bytes()),
+            Encoding::Utf16Le => bytes.extend(s.encode_utf16().flat_map(u16::to_le_bytes)),
+            Encoding::Utf16Be => bytes.extend(s.encode_utf16().flat_map(u16::to_be_bytes)),
+        }
+
+        bytes
+    }
+}
+
+pub(crate) fn decode_utf8(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).map_err(|err| {
+        let valid = std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap_or_default();
+        Error::from(ErrorKind::InvalidUtf8).or_position(|| Position::new(valid, valid.len()))
+    })
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<String> {
+    let chunks = bytes.chunks_exact(2);
+    let odd = !chunks.remainder().is_empty();
+
+    let mut s = String::with_capacity(bytes.len() / 2);
+    for ch in char::decode_utf16(chunks.map(|x| from_bytes([x[0], x[1]]))) {
+        s.push(ch.map_err(|_| invalid_utf16(&s))?);
+    }
+
+    match odd {
+        true => Err(invalid_utf16(&s)),
+        false => Ok(s),
+    }
+}
+
+fn invalid_utf16(valid: &str) -> Error {
+    Error::from(ErrorKind::InvalidUtf16).or_position(|| Position::new(valid, valid.len()))
+}
@@ -2,25 +2,102 @@ use std::fmt::Display;
 
 use serde::{de, ser};
 
+use crate::encoding::Encoding;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
-pub enum Error {
+pub struct Error {
+    kind: ErrorKind,
+    position: Option<Position>,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
     Message(String),
     Eof,
     UnsupportedType,
     TrailingCharacters,
-    ExpectedBoolean,
-    ExpectedString,
-    ExpectedInteger,
-    NonSelfDescribing,
-    ExpectedArray,
-    ExpectedArrayEnd,
-    ArrayIndex,
-    ExpectedMap,
-    ExpectedMapEnd,
-    MapSyntax,
-    SeqSyntax,
+    UnterminatedComment,
+    ExpectedBoolean(String),
+    ExpectedString(char),
+    ExpectedInteger(String),
+    ExpectedFloat(String),
+    ExpectedArray(char),
+    ExpectedArrayEnd(char),
+    ArrayIndex { expected: usize, found: usize },
+    ExpectedMap(char),
+    ExpectedMapEnd(char),
+    DuplicateKey(String),
+    KeyMustBeAString,
+    SeqSyntax(char),
+    ConditionSyntax(String),
+    RecursionLimitExceeded,
+    InvalidUtf8,
+    InvalidUtf16,
+    UnsupportedEncoding(Encoding),
+    Io(std::io::Error),
+    IncludeCycle(String),
+}
+
+/// A location in the deserializer's input. Lines and columns start at 1, and columns count
+/// characters rather than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub(crate) fn new(input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl Error {
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn into_kind(self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Where in the input the error occurred. Serialization errors have no position.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Drops the position, for errors found in text that the caller never saw.
+    pub(crate) fn without_position(mut self) -> Self {
+        self.position = None;
+        self
+    }
+
+    /// Attaches a position, unless the error already has one.
+    pub(crate) fn or_position(mut self, position: impl FnOnce() -> Position) -> Self {
+        if self.position.is_none() {
+            self.position = Some(position());
+        }
+        self
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            position: None,
+        }
+    }
 }
 
 impl ser::Error for Error {
@@ -28,7 +105,7 @@ impl ser::Error for Error {
     where
         T: Display,
     {
-        Error::Message(msg.to_string())
+        ErrorKind::Message(msg.to_string()).into()
     }
 }
 
@@ -37,19 +114,74 @@ impl de::Error for Error {
     where
         T: Display,
     {
-        Error::Message(msg.to_string())
+        ErrorKind::Message(msg.to_string()).into()
     }
 }
 
-impl Display for Error {
+impl Display for ErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::Message(msg) => f.write_str(msg),
-            Error::Eof => f.write_str("unexpected end of string"),
-            Error::UnsupportedType => f.write_str("unsupported data type"),
-            _ => todo!(),
+            ErrorKind::Message(msg) => f.write_str(msg),
+            ErrorKind::Eof => f.write_str("unexpected end of string"),
+            ErrorKind::UnsupportedType => f.write_str("unsupported data type"),
+            ErrorKind::TrailingCharacters => f.write_str("trailing characters after the document"),
+            ErrorKind::UnterminatedComment => f.write_str("unterminated `/*` comment"),
+            ErrorKind::ExpectedBoolean(found) => {
+                write!(f, "expected boolean, found {found:?}")
+            }
+            ErrorKind::ExpectedString(found) => write!(f, "expected string, found `{found}`"),
+            ErrorKind::ExpectedInteger(found) => write!(f, "expected number, found {found:?}"),
+            ErrorKind::ExpectedFloat(found) => write!(f, "expected float, found {found:?}"),
+            ErrorKind::ExpectedArray(found) => {
+                write!(f, "expected `{{` of an array, found `{found}`")
+            }
+            ErrorKind::ExpectedArrayEnd(found) => {
+                write!(f, "expected `}}` after an array, found `{found}`")
+            }
+            ErrorKind::ArrayIndex { expected, found } => {
+                write!(f, "expected array index {expected}, found {found}")
+            }
+            ErrorKind::ExpectedMap(found) => write!(f, "expected `{{` of a block, found `{found}`"),
+            ErrorKind::ExpectedMapEnd(found) => {
+                write!(f, "expected `}}` after a block, found `{found}`")
+            }
+            ErrorKind::DuplicateKey(key) => write!(f, "duplicate key {key:?}"),
+            ErrorKind::KeyMustBeAString => f.write_str("key must be a string"),
+            ErrorKind::SeqSyntax(found) => write!(f, "expected array index, found `{found}`"),
+            ErrorKind::ConditionSyntax(condition) => {
+                write!(f, "invalid conditional expression [{condition}]")
+            }
+            ErrorKind::RecursionLimitExceeded => f.write_str("recursion limit exceeded"),
+            ErrorKind::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            ErrorKind::InvalidUtf16 => f.write_str("invalid UTF-16"),
+            ErrorKind::UnsupportedEncoding(encoding) => {
+                write!(
+                    f,
+                    "cannot borrow from {encoding:?} input, read it with from_reader"
+                )
+            }
+            ErrorKind::Io(err) => write!(f, "{err}"),
+            ErrorKind::IncludeCycle(path) => write!(f, "{path} includes itself"),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some(Position { line, column, .. }) => {
+                write!(f, "{} at line {line} column {column}", self.kind)
+            }
+            None => self.kind.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
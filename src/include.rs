@@ -0,0 +1,366 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    de::Deserializer,
+    error::{Error, ErrorKind, Result},
+    options::{options, DeserializerOptions},
+    ser::write_escaped,
+};
+
+/// Loads the files named by `#base` and `#include` directives.
+///
+/// Like in Source, the path of a directive is relative to the file that contains it, so
+/// `#base "base.res"` in `ui/hud.res` names `ui/base.res`. Paths are passed to the resolver
+/// with `/` separators and without `.` or inner `..` components.
+pub trait Resolver {
+    /// Returns the contents of the file at `path`.
+    fn resolve(&self, path: &str) -> io::Result<String>;
+}
+
+/// Resolves directives against files in a directory. Absolute paths and paths that climb out
+/// of the directory with `..` are rejected, while `..` that stays inside it is allowed.
+#[derive(Debug, Clone)]
+pub struct FileResolver {
+    root: PathBuf,
+}
+
+impl FileResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileResolver { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Resolver for FileResolver {
+    fn resolve(&self, path: &str) -> io::Result<String> {
+        let relative = PathBuf::from(normalize(path));
+        let inside_root = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        if !inside_root {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{path} is outside of {}", self.root.display()),
+            ));
+        }
+
+        fs::read_to_string(self.root.join(relative))
+    }
+}
+
+/// Resolves directives against a set of in-memory files, which is mostly useful for tests.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+    files: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: impl Into<String>, contents: impl Into<String>) -> &mut Self {
+        self.files.insert(path.into(), contents.into());
+        self
+    }
+}
+
+impl Resolver for MemoryResolver {
+    fn resolve(&self, path: &str) -> io::Result<String> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{path} was not found")))
+    }
+}
+
+impl<R> Resolver for &R
+where
+    R: Resolver + ?Sized,
+{
+    fn resolve(&self, path: &str) -> io::Result<String> {
+        (**self).resolve(path)
+    }
+}
+
+/// Deserializes a document after resolving its `#base` and `#include` directives.
+///
+/// See [`Deserializer::expand_includes`] for how the files are merged. A document without
+/// directives is read as it is. Otherwise the merged document is read, and errors found in it
+/// have no [`position`](Error::position), since it is not the text that was passed in.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use better_vdf::MemoryResolver;
+///
+/// let mut resolver = MemoryResolver::new();
+/// resolver.insert("base.res", r#""Hud" { "tall" "10" "wide" "20" }"#);
+///
+/// let hud: HashMap<String, HashMap<String, u32>> = better_vdf::from_str_with_resolver(
+///     "#base \"base.res\"\n\"Hud\" { \"tall\" \"15\" }",
+///     &resolver,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(hud["Hud"]["tall"], 15);
+/// assert_eq!(hud["Hud"]["wide"], 20);
+/// ```
+pub fn from_str_with_resolver<T, R>(s: &str, resolver: R) -> Result<T>
+where
+    T: DeserializeOwned,
+    R: Resolver,
+{
+    options().from_str_with_resolver(s, resolver)
+}
+
+impl DeserializerOptions {
+    /// Deserializes a document with these options after resolving its directives, like
+    /// [`crate::from_str_with_resolver`].
+    pub fn from_str_with_resolver<T, R>(&self, s: &str, resolver: R) -> Result<T>
+    where
+        T: DeserializeOwned,
+        R: Resolver,
+    {
+        let mut de = self.deserializer(s);
+        let (entries, directives) = de.parse_document()?;
+        if directives.is_empty() {
+            return self.from_str(s);
+        }
+
+        let entries = de.resolve_directives(entries, directives, &resolver, &mut Vec::new())?;
+        let mut merged = String::new();
+        write_entries(&mut merged, &entries, 0);
+        self.from_str(&merged).map_err(Error::without_position)
+    }
+}
+
+struct Entry {
+    key: String,
+    condition: Option<String>,
+    value: Node,
+}
+
+/// A `#base` or `#include` directive and the path it names.
+struct Directive {
+    base: bool,
+    path: String,
+}
+
+enum Node {
+    String(String),
+    Block(Vec<Entry>),
+}
+
+impl<'de> Deserializer<'de> {
+    /// Resolves the `#base` and `#include` directives at the root of the document, returning
+    /// the merged document.
+    ///
+    /// Entries of an `#include`d file are appended after the document's own entries, while a
+    /// `#base` file only contributes the keys the document does not already define. Included
+    /// files are parsed with the same settings as this deserializer, and conditionals are
+    /// evaluated along the way.
+    pub fn expand_includes<R>(&self, resolver: R) -> Result<String>
+    where
+        R: Resolver,
+    {
+        let mut de = self.with_input(self.input);
+        let entries = de.expand(&resolver, &mut Vec::new())?;
+
+        let mut output = String::new();
+        write_entries(&mut output, &entries, 0);
+        Ok(output)
+    }
+
+    fn expand(&mut self, resolver: &dyn Resolver, stack: &mut Vec<String>) -> Result<Vec<Entry>> {
+        let (entries, directives) = self.parse_document()?;
+        self.resolve_directives(entries, directives, resolver, stack)
+    }
+
+    /// Parses a whole document, separating its directives from its entries.
+    fn parse_document(&mut self) -> Result<(Vec<Entry>, Vec<Directive>)> {
+        let mut entries = Vec::new();
+        let mut directives = Vec::new();
+
+        let parsed = self.parse_entries()?;
+        self.skip_whitespace();
+        if !self.input.is_empty() {
+            self.peek_real_char()?;
+            if !self.options.trailing_characters {
+                return Err(self.error(ErrorKind::TrailingCharacters));
+            }
+        }
+
+        for entry in parsed {
+            match (&entry.value, entry.key.to_ascii_lowercase().as_str()) {
+                (Node::String(path), "#base") => directives.push(Directive {
+                    base: true,
+                    path: path.clone(),
+                }),
+                (Node::String(path), "#include") => directives.push(Directive {
+                    base: false,
+                    path: path.clone(),
+                }),
+                _ => entries.push(entry),
+            }
+        }
+
+        Ok((entries, directives))
+    }
+
+    fn resolve_directives(
+        &self,
+        mut entries: Vec<Entry>,
+        directives: Vec<Directive>,
+        resolver: &dyn Resolver,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<Entry>> {
+        for Directive { base, path } in directives {
+            let path = join(stack.last().map(String::as_str), &path);
+            if stack.contains(&path) {
+                return Err(ErrorKind::IncludeCycle(path).into());
+            }
+
+            let text = resolver
+                .resolve(&path)
+                .map_err(|err| Error::from(ErrorKind::Io(err)))?;
+            stack.push(path);
+            let included = self.with_input(&text).expand(resolver, stack)?;
+            stack.pop();
+
+            if base {
+                merge(&mut entries, included);
+            } else {
+                entries.extend(included);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn parse_entries(&mut self) -> Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+
+        loop {
+            self.skip_disabled_entries()?;
+            if matches!(self.peek_real_char(), Ok('}') | Err(_)) {
+                return Ok(entries);
+            }
+
+            let key = self.parse_string()?.into_owned();
+            let mut condition = self.parse_condition()?;
+            let value = if self.peek_real_char()? == '{' {
+                self.open_block(ErrorKind::ExpectedMap)?;
+                let entries = self.parse_entries()?;
+                self.close_block(ErrorKind::ExpectedMapEnd)?;
+                Node::Block(entries)
+            } else {
+                Node::String(self.parse_string()?.into_owned())
+            };
+            condition = self.parse_condition()?.or(condition);
+
+            entries.push(Entry {
+                key,
+                condition: condition
+                    .filter(|_| self.options.preserve_conditionals)
+                    .map(str::to_owned),
+                value,
+            });
+        }
+    }
+}
+
+/// Joins the path of a directive to the directory of the file that contains it, if any.
+fn join(from: Option<&str>, path: &str) -> String {
+    let dir = match from {
+        Some(from) if !path.starts_with(['/', '\\']) && !Path::new(path).is_absolute() => {
+            from.rfind('/').map_or("", |x| &from[..=x])
+        }
+        _ => "",
+    };
+
+    normalize(&format!("{dir}{path}"))
+}
+
+/// Removes `.` components and the components that `..` climbs out of, keeping the `..` that
+/// would climb above the start of the path.
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut components: Vec<&str> = Vec::new();
+
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." if components.last().is_some_and(|&x| x != "..") => {
+                components.pop();
+            }
+            _ => components.push(component),
+        }
+    }
+
+    let normalized = components.join("/");
+    match path.starts_with('/') {
+        true => format!("/{normalized}"),
+        false => normalized,
+    }
+}
+
+fn merge(entries: &mut Vec<Entry>, base: Vec<Entry>) {
+    for entry in base {
+        match entries
+            .iter_mut()
+            .find(|x| x.key.eq_ignore_ascii_case(&entry.key))
+        {
+            Some(existing) => {
+                if let (Node::Block(existing), Node::Block(base)) =
+                    (&mut existing.value, entry.value)
+                {
+                    merge(existing, base);
+                }
+            }
+            None => entries.push(entry),
+        }
+    }
+}
+
+fn write_entries(output: &mut String, entries: &[Entry], depth: usize) {
+    for entry in entries {
+        *output += &"\t".repeat(depth);
+        write_escaped(output, &entry.key);
+
+        match &entry.value {
+            Node::String(value) => {
+                *output += "\t\t";
+                write_escaped(output, value);
+                write_condition(output, &entry.condition);
+                *output += "\n";
+            }
+            Node::Block(entries) => {
+                write_condition(output, &entry.condition);
+                *output += "\n";
+                *output += &"\t".repeat(depth);
+                *output += "{\n";
+                write_entries(output, entries, depth + 1);
+                *output += &"\t".repeat(depth);
+                *output += "}\n";
+            }
+        }
+    }
+}
+
+fn write_condition(output: &mut String, condition: &Option<String>) {
+    if let Some(condition) = condition {
+        *output += " [";
+        *output += condition;
+        *output += "]";
+    }
+}
@@ -0,0 +1,52 @@
+//! Reads and writes a sequence as a block of indexed entries, for fields that should keep that
+//! layout when [`SeqStyle::Repeated`](crate::SeqStyle::Repeated) is the default.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use better_vdf::{SeqStyle, Serializer};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Hud {
+//!     item: Vec<String>,
+//!     #[serde(with = "better_vdf::indexed")]
+//!     order: Vec<u32>,
+//! }
+//!
+//! let hud = Hud {
+//!     item: vec!["a".to_owned(), "b".to_owned()],
+//!     order: vec![1, 0],
+//! };
+//!
+//! let mut output = Vec::new();
+//! hud.serialize(&mut Serializer::new(&mut output).seq_style(SeqStyle::Repeated))
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     String::from_utf8(output).unwrap(),
+//!     "\"item\"\t\t\"a\"\n\"item\"\t\t\"b\"\n\"order\"\t\t{\n\t\"0\"\t\t\"1\"\n\t\"1\"\t\t\"0\"\n}\n",
+//! );
+//! ```
+
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::repeated::SeqVisitor;
+
+pub(crate) const INDEXED: &str = "$better_vdf::private::Indexed";
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + Serialize,
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(INDEXED, value)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(INDEXED, SeqVisitor(PhantomData))
+}
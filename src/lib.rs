@@ -69,10 +69,30 @@
 //!
 //! ```
 
+#![allow(clippy::tabs_in_doc_comments)]
+
+mod condition;
 mod de;
+mod encoding;
 mod error;
+mod include;
+pub mod indexed;
+pub mod number;
+mod options;
+pub mod repeated;
+mod root;
 mod ser;
+mod value;
 
-pub use de::{from_str, Deserializer};
-pub use error::{Error, Result};
-pub use ser::{to_string, Serializer};
+pub use condition::Conditional;
+pub use de::{from_reader, from_slice, from_str, Deserializer, DuplicateKeys, KeyCase};
+pub use encoding::Encoding;
+pub use error::{Error, ErrorKind, Position, Result};
+pub use include::{from_str_with_resolver, FileResolver, MemoryResolver, Resolver};
+pub use options::{options, BoolEncoding, DeserializerOptions, Whitespace};
+pub use root::{from_str_with_root, to_string_with_root};
+pub use ser::{
+    to_string, to_string_pretty, to_vec, to_writer, to_writer_pretty, to_writer_with_encoding,
+    FloatFormat, PrettyConfig, SeqStyle, Serializer,
+};
+pub use value::{Object, Value};
@@ -0,0 +1,94 @@
+//! Reads a number from a string, for fields that serde buffers before deserializing them, such
+//! as the fields of internally tagged and untagged enums.
+//!
+//! Buffered content only sees what [`deserialize_any`](serde::Deserializer::deserialize_any)
+//! reports, and VDF values are always strings, so a plain `u32` field inside such an enum fails
+//! with "invalid type: string". This helper parses the string instead.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! #[serde(tag = "type")]
+//! enum Item {
+//!     Weapon {
+//!         #[serde(with = "better_vdf::number")]
+//!         damage: u32,
+//!     },
+//! }
+//!
+//! let items: BTreeMap<String, Item> =
+//!     better_vdf::from_str(r#""0" { "type" "Weapon" "damage" "30" }"#).unwrap();
+//!
+//! assert_eq!(items["0"], Item::Weapon { damage: 30 });
+//! ```
+//!
+//! Other formats see the number as it is.
+
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use serde::{
+    de::{self, IntoDeserializer, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + Serialize,
+    S: Serializer,
+{
+    value.serialize(serializer)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(NumberVisitor(PhantomData))
+}
+
+/// Parses strings with [`FromStr`] and hands numbers to `T` unchanged.
+struct NumberVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for NumberVisitor<T>
+where
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a number or a string containing one")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        T::deserialize(v.into_deserializer())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        T::deserialize(v.into_deserializer())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        T::deserialize(v.into_deserializer())
+    }
+}
@@ -0,0 +1,238 @@
+use std::io;
+
+use serde::de::{Deserialize, DeserializeOwned};
+
+use crate::{
+    de::{Deserializer, DuplicateKeys, KeyCase},
+    encoding::{decode_utf8, Encoding},
+    error::{Error, ErrorKind, Result},
+    ser::SeqStyle,
+};
+
+/// Returns the default deserializer options, which can be adjusted before reading a document.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// let vdf = "{ \"ready\" \"true\" \"list\" { \"1\" \"a\" \"2\" \"b\" } } // end\n}";
+///
+/// assert!(better_vdf::from_str::<HashMap<String, better_vdf::Value>>(vdf).is_err());
+///
+/// #[derive(serde::Deserialize)]
+/// struct Doc {
+///     ready: bool,
+///     list: Vec<String>,
+/// }
+///
+/// let doc: Doc = better_vdf::options().lenient().from_str(vdf).unwrap();
+///
+/// assert!(doc.ready);
+/// assert_eq!(doc.list, ["a", "b"]);
+/// ```
+pub fn options() -> DeserializerOptions {
+    DeserializerOptions::default()
+}
+
+/// The settings of a [`Deserializer`]. The defaults read documents the way the Source engine
+/// writes them, while [`lenient`](Self::lenient) accepts most hand-edited files.
+#[derive(Debug, Clone, Default)]
+pub struct DeserializerOptions {
+    pub(crate) whitespace: Whitespace,
+    pub(crate) block_comments: bool,
+    pub(crate) braced_root: bool,
+    pub(crate) bools: BoolEncoding,
+    pub(crate) trailing_characters: bool,
+    pub(crate) symbols: Vec<String>,
+    pub(crate) preserve_conditionals: bool,
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) seq_style: SeqStyle,
+    pub(crate) lenient_indices: bool,
+    pub(crate) key_case: KeyCase,
+    pub(crate) empty_as_none: bool,
+}
+
+/// The characters skipped between tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Whitespace {
+    /// Spaces, tabs, `\r` and `\n`.
+    #[default]
+    Ascii,
+    /// Any character that Unicode considers whitespace, such as non-breaking spaces.
+    Unicode,
+}
+
+impl Whitespace {
+    pub(crate) fn contains(self, ch: char) -> bool {
+        match self {
+            Whitespace::Ascii => ch == '\t' || ch == '\n' || ch == '\r' || ch == ' ',
+            Whitespace::Unicode => ch.is_whitespace(),
+        }
+    }
+}
+
+/// The strings accepted as booleans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoolEncoding {
+    /// `"1"` and `"0"`, which is also how booleans are written.
+    #[default]
+    Numeric,
+    /// `"1"` and `"0"` as well as `"true"` and `"false"` in any case.
+    Lenient,
+}
+
+impl BoolEncoding {
+    pub(crate) fn parse(self, s: &str) -> Option<bool> {
+        match (self, s) {
+            (_, "1") => Some(true),
+            (_, "0") => Some(false),
+            (BoolEncoding::Lenient, s) if s.eq_ignore_ascii_case("true") => Some(true),
+            (BoolEncoding::Lenient, s) if s.eq_ignore_ascii_case("false") => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl DeserializerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts what hand-edited files tend to contain: any Unicode whitespace, block comments,
+    /// a root wrapped in braces, indexed sequences with unusual keys, `true` and `false` for
+    /// booleans and anything after the document.
+    pub fn lenient(self) -> Self {
+        self.whitespace(Whitespace::Unicode)
+            .block_comments(true)
+            .braced_root(true)
+            .lenient_indices(true)
+            .bools(BoolEncoding::Lenient)
+            .trailing_characters(true)
+    }
+
+    /// Sets the characters skipped between tokens.
+    pub fn whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// See [`Deserializer::block_comments`].
+    pub fn block_comments(mut self, enabled: bool) -> Self {
+        self.block_comments = enabled;
+        self
+    }
+
+    /// Accepts a root wrapped in `{ }` as well as the usual unbraced list of entries.
+    pub fn braced_root(mut self, enabled: bool) -> Self {
+        self.braced_root = enabled;
+        self
+    }
+
+    /// Sets the strings accepted as booleans.
+    pub fn bools(mut self, encoding: BoolEncoding) -> Self {
+        self.bools = encoding;
+        self
+    }
+
+    /// Ignores anything after the document instead of failing with
+    /// [`ErrorKind::TrailingCharacters`].
+    pub fn trailing_characters(mut self, allowed: bool) -> Self {
+        self.trailing_characters = allowed;
+        self
+    }
+
+    /// See [`Deserializer::define`].
+    pub fn define(mut self, symbol: impl Into<String>) -> Self {
+        self.symbols.push(symbol.into());
+        self
+    }
+
+    /// See [`Deserializer::preserve_conditionals`].
+    pub fn preserve_conditionals(mut self, enabled: bool) -> Self {
+        self.preserve_conditionals = enabled;
+        self
+    }
+
+    /// See [`Deserializer::duplicate_keys`].
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// See [`Deserializer::seq_style`].
+    pub fn seq_style(mut self, style: SeqStyle) -> Self {
+        self.seq_style = style;
+        self
+    }
+
+    /// See [`Deserializer::lenient_indices`].
+    pub fn lenient_indices(mut self, enabled: bool) -> Self {
+        self.lenient_indices = enabled;
+        self
+    }
+
+    /// See [`Deserializer::key_case`].
+    pub fn key_case(mut self, key_case: KeyCase) -> Self {
+        self.key_case = key_case;
+        self
+    }
+
+    /// See [`Deserializer::empty_as_none`].
+    pub fn empty_as_none(mut self, enabled: bool) -> Self {
+        self.empty_as_none = enabled;
+        self
+    }
+
+    /// Creates a deserializer for `input` with these options.
+    pub fn deserializer<'de>(&self, input: &'de str) -> Deserializer<'de> {
+        Deserializer::with_options(input, self.clone())
+    }
+
+    /// Deserializes a document with these options, like [`crate::from_str`].
+    pub fn from_str<'a, T>(&self, s: &'a str) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        let mut deserializer = self.deserializer(s);
+        let t = T::deserialize(&mut deserializer).map_err(|err| deserializer.fix_position(err))?;
+        deserializer.skip_whitespace();
+        if deserializer.input.is_empty() {
+            return Ok(t);
+        }
+
+        // Reports an unterminated comment even where trailing characters are allowed.
+        deserializer.peek_real_char()?;
+        match self.trailing_characters {
+            true => Ok(t),
+            false => Err(deserializer.error(ErrorKind::TrailingCharacters)),
+        }
+    }
+
+    /// Deserializes UTF-8 bytes with these options, like [`crate::from_slice`].
+    pub fn from_slice<'a, T>(&self, v: &'a [u8]) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        match Encoding::detect(v) {
+            encoding @ (Encoding::Utf8 | Encoding::Utf8Bom) => {
+                let v = v.strip_prefix(encoding.bom()).unwrap_or(v);
+                self.from_str(decode_utf8(v)?)
+            }
+            encoding => Err(ErrorKind::UnsupportedEncoding(encoding).into()),
+        }
+    }
+
+    /// Deserializes a document read from `reader` with these options, like
+    /// [`crate::from_reader`].
+    pub fn from_reader<R, T>(&self, mut reader: R) -> Result<T>
+    where
+        R: io::Read,
+        T: DeserializeOwned,
+    {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .map_err(|err| Error::from(ErrorKind::Io(err)))?;
+
+        self.from_str(&Encoding::detect(&buf).decode(&buf)?)
+    }
+}
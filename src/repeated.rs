@@ -0,0 +1,84 @@
+//! Reads and writes a sequence as the same key repeated once per element, instead of a block
+//! of indexed entries.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Hud {
+//!     #[serde(with = "better_vdf::repeated")]
+//!     item: Vec<String>,
+//!     name: String,
+//! }
+//!
+//! let hud: Hud = better_vdf::from_str(r#""item" "first" "name" "hud" "item" "second""#).unwrap();
+//!
+//! assert_eq!(hud.item, ["first", "second"]);
+//! assert_eq!(
+//!     better_vdf::to_string(&hud).unwrap(),
+//!     "\"item\"\t\t\"first\"\n\"item\"\t\t\"second\"\n\"name\"\t\t\"hud\"\n",
+//! );
+//! ```
+//!
+//! Add `#[serde(default)]` if the key may be missing altogether. Other formats see the
+//! sequence as it is. To write every sequence this way, see
+//! [`Serializer::seq_style`](crate::Serializer::seq_style) and
+//! [`Deserializer::seq_style`](crate::Deserializer::seq_style).
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+pub(crate) const REPEATED: &str = "$better_vdf::private::Repeated";
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + Serialize,
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(REPEATED, value)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(REPEATED, SeqVisitor(PhantomData))
+}
+
+/// Collects a sequence, either directly or from the newtype that other formats present the
+/// field helpers as.
+pub(crate) struct SeqVisitor<T>(pub(crate) PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for SeqVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
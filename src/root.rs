@@ -0,0 +1,103 @@
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Serialize,
+};
+
+use crate::{de::from_str, error::Result, ser::to_string};
+
+/// Deserializes a document made of a single named block, returning the name of the block
+/// along with its contents.
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct AppState {
+///     appid: u32,
+///     name: String,
+/// }
+///
+/// let (root, state) = better_vdf::from_str_with_root::<AppState>(
+///     r#""AppState" { "appid" "228980" "name" "Steamworks Common Redistributables" }"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(root, "AppState");
+/// assert_eq!(state.appid, 228980);
+/// ```
+pub fn from_str_with_root<'a, T>(s: &'a str) -> Result<(String, T)>
+where
+    T: Deserialize<'a>,
+{
+    from_str::<Root<String, T>>(s).map(|root| (root.name, root.value))
+}
+
+/// Serializes `value` as a block named `root`, the inverse of [`from_str_with_root`].
+pub fn to_string_with_root<T>(root: &str, value: &T) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    to_string(&Root { name: root, value })
+}
+
+struct Root<N, T> {
+    name: N,
+    value: T,
+}
+
+impl<T> Serialize for Root<&str, &T>
+where
+    T: ?Sized + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(self.name, self.value)?;
+        map.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Root<String, T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(RootVisitor(PhantomData))
+    }
+}
+
+struct RootVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for RootVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Root<String, T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a single root block")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let (name, value) = map
+            .next_entry()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        if map.next_key::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+
+        Ok(Root { name, value })
+    }
+}
@@ -1,25 +1,539 @@
-use serde::Serialize;
+use std::{borrow::Cow, io};
+
+use serde::{ser::Impossible, Serialize};
+
+use crate::{
+    condition::CONDITIONAL,
+    encoding::Encoding,
+    error::{Error, ErrorKind, Result},
+    indexed::INDEXED,
+    repeated::REPEATED,
+};
+
+/// Serializes values as VDF text into an [`io::Write`].
+///
+/// Output is written piece by piece, so wrap unbuffered writers such as [`std::fs::File`] in a
+/// [`io::BufWriter`].
+pub struct Serializer<W> {
+    writer: W,
+    frames: Vec<Frame>,
+    config: PrettyConfig,
+    at_root: bool,
+    /// Whether a line was ended, which is written before anything else so that the end of
+    /// the document can leave it out.
+    newline: bool,
+    /// The key of the entry being written, held back until its value writes something.
+    key: Option<String>,
+    seq_style: SeqStyle,
+    /// The style of the next sequence, as chosen by a [`crate::repeated`] or
+    /// [`crate::indexed`] field.
+    next_seq: Option<SeqStyle>,
+}
+
+/// How sequences are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeqStyle {
+    /// A block of entries keyed by their index, `"list" { "0" "a" "1" "b" }`.
+    #[default]
+    Indexed,
+    /// The key of the sequence repeated once per element, `"list" "a" "list" "b"`. Sequences
+    /// that are not the value of an entry, such as the elements of such a sequence, are still
+    /// indexed.
+    Repeated,
+}
+
+/// Layout options for [`to_string_pretty`], [`to_writer_pretty`] and [`Serializer::pretty`].
+///
+/// The defaults match [`to_string`]:
+///
+/// ```text
+/// "name"		"value"
+/// "block"		{
+/// 	"key"		"value"
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrettyConfig {
+    indent: String,
+    separator: String,
+    align: Option<usize>,
+    newline: String,
+    final_newline: bool,
+    braces_on_own_line: bool,
+    float_format: FloatFormat,
+}
+
+/// How floats are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// Every digit written out without an exponent, as Rust's `Display` does, such as `1`,
+    /// `0.1` or `0.0000001`.
+    #[default]
+    Display,
+    /// The shortest text that reads back as the same number, using an exponent when that is
+    /// shorter, such as `1`, `0.1`, `1e-7` or `1e3`.
+    Shortest,
+    /// A fixed number of decimals, so that `Fixed(2)` writes `1.50`.
+    Fixed(usize),
+    /// Six decimals like C's `%f`, which is how the Source engine writes floats, `1.000000`.
+    Valve,
+}
+
+impl FloatFormat {
+    pub(crate) fn format<F>(self, v: F) -> String
+    where
+        F: std::fmt::Display + std::fmt::LowerExp,
+    {
+        match self {
+            FloatFormat::Display => v.to_string(),
+            FloatFormat::Shortest => {
+                let plain = v.to_string();
+                let exponent = format!("{v:e}");
+                match exponent.len() < plain.len() {
+                    true => exponent,
+                    false => plain,
+                }
+            }
+            FloatFormat::Fixed(decimals) => format!("{v:.decimals$}"),
+            FloatFormat::Valve => match format!("{v:.6}") {
+                nan if nan == "NaN" => "nan".to_owned(),
+                s => s,
+            },
+        }
+    }
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        PrettyConfig {
+            indent: "\t".to_owned(),
+            separator: "\t\t".to_owned(),
+            align: None,
+            newline: "\n".to_owned(),
+            final_newline: true,
+            braces_on_own_line: false,
+            float_format: FloatFormat::Display,
+        }
+    }
+}
+
+impl PrettyConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the whole document on a single line, `"a" "1" "b" { "c" "2" }`.
+    pub fn compact() -> Self {
+        PrettyConfig::new()
+            .indent("")
+            .separator(" ")
+            .newline(" ")
+            .final_newline(false)
+    }
+
+    /// The layout of the files that Steam writes, such as `libraryfolders.vdf`: braces on
+    /// their own lines, two tabs between a key and its value, and [`FloatFormat::Valve`]
+    /// floats.
+    ///
+    /// Steam does not align values to the longest key of a block, but always writes two tabs,
+    /// so aligning them would itself produce diffs. Use [`PrettyConfig::align_values`] for
+    /// files meant to be read by people.
+    pub fn steam() -> Self {
+        PrettyConfig::new()
+            .braces_on_own_line(true)
+            .float_format(FloatFormat::Valve)
+    }
+
+    /// The string written once per nesting level before each line. Defaults to a tab.
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// The string written between a key and its value. Defaults to two tabs.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Pads keys with spaces so that values start `column` characters after the indentation.
+    /// Keys that are too long for that are followed by the separator instead.
+    pub fn align_values(mut self, column: usize) -> Self {
+        self.align = Some(column);
+        self
+    }
+
+    /// The string that ends each line. Defaults to `\n`, use `\r\n` for CRLF files.
+    pub fn newline(mut self, newline: impl Into<String>) -> Self {
+        self.newline = newline.into();
+        self
+    }
+
+    /// Whether the last line of the document is ended like the others. Defaults to `true`.
+    pub fn final_newline(mut self, enabled: bool) -> Self {
+        self.final_newline = enabled;
+        self
+    }
+
+    /// Writes the `{` of a block on its own line below the key, as the Source engine does,
+    /// instead of after the key.
+    pub fn braces_on_own_line(mut self, enabled: bool) -> Self {
+        self.braces_on_own_line = enabled;
+        self
+    }
+
+    /// Sets how floats are written. Defaults to [`FloatFormat::Display`].
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+}
+
+impl<W> Serializer<W>
+where
+    W: io::Write,
+{
+    pub fn new(writer: W) -> Self {
+        Self::pretty(writer, PrettyConfig::default())
+    }
+
+    pub fn pretty(writer: W, config: PrettyConfig) -> Self {
+        Serializer {
+            writer,
+            frames: Vec::new(),
+            config,
+            at_root: true,
+            newline: false,
+            key: None,
+            seq_style: SeqStyle::Indexed,
+            next_seq: None,
+        }
+    }
+
+    /// Sets how sequences are written, unless a field overrides it with [`crate::repeated`] or
+    /// [`crate::indexed`].
+    pub fn seq_style(mut self, style: SeqStyle) -> Self {
+        self.seq_style = style;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes `s`, after ending the previous line if needed.
+    fn write(&mut self, s: &str) -> Result<()> {
+        if std::mem::take(&mut self.newline) {
+            self.writer
+                .write_all(self.config.newline.as_bytes())
+                .map_err(|err| Error::from(ErrorKind::Io(err)))?;
+        }
+
+        self.writer
+            .write_all(s.as_bytes())
+            .map_err(|err| Error::from(ErrorKind::Io(err)))
+    }
+
+    fn write_indent(&mut self) -> Result<()> {
+        let depth = self
+            .frames
+            .iter()
+            .filter(|x| matches!(x, Frame::Block(_)))
+            .count();
+
+        self.write(&self.config.indent.repeat(depth))
+    }
+
+    /// Writes the pending key, if any, before the value of an entry.
+    fn write_key(&mut self) -> Result<()> {
+        if let Some(key) = self.key.take() {
+            self.write_indent()?;
+            self.write(&key)?;
+
+            match self.config.align {
+                Some(column) if key.chars().count() < column => {
+                    self.write(&" ".repeat(column - key.chars().count()))?
+                }
+                _ => self.write(&self.config.separator.clone())?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a quoted string as the value of the pending entry.
+    fn write_str(&mut self, v: &str) -> Result<()> {
+        self.write_key()?;
+        self.write("\"")?;
+        self.write(&escape(v))?;
+        self.write("\"")?;
+
+        match self.take_condition() {
+            Some(condition) => self.write(&format!(" [{condition}]")),
+            None => Ok(()),
+        }
+    }
+
+    /// Takes the condition of the [`crate::Conditional`] whose value is being written.
+    fn take_condition(&mut self) -> Option<String> {
+        match self.frames.last_mut() {
+            Some(Frame::Conditional(condition)) => condition.take(),
+            _ => None,
+        }
+    }
+
+    /// Ends the line of an entry, unless nothing was written for its value and the key is
+    /// still pending.
+    fn end_entry(&mut self) -> Result<()> {
+        if self.key.take().is_none() {
+            self.newline = true;
+        }
+        Ok(())
+    }
+
+    fn begin_block(&mut self) -> Result<()> {
+        self.at_root = false;
+
+        // Conditions of blocks go between the key and the `{`.
+        if let (Some(condition), Some(key)) = (self.take_condition(), self.key.as_mut()) {
+            *key += &format!(" [{condition}]");
+        }
+
+        match self.key.take() {
+            Some(key) if self.config.braces_on_own_line => {
+                self.write_indent()?;
+                self.write(&key)?;
+                self.newline = true;
+                self.write_indent()?;
+            }
+            key => {
+                self.key = key;
+                self.write_key()?;
+            }
+        }
+
+        self.write("{")?;
+        self.newline = true;
+        self.frames.push(Frame::Block(0));
+        Ok(())
+    }
+
+    /// Like [`Serializer::begin_block`], except that the root map is written as a plain list
+    /// of entries, the way [`crate::from_str`] reads it.
+    fn begin_map(&mut self) -> Result<()> {
+        match self.at_root {
+            true => {
+                self.at_root = false;
+                Ok(())
+            }
+            false => self.begin_block(),
+        }
+    }
+
+    /// Begins a sequence, either as a block of indexed entries or as one entry per element
+    /// under the pending key.
+    fn begin_seq(&mut self) -> Result<()> {
+        let style = self.next_seq.take().unwrap_or(self.seq_style);
+        let nested = matches!(self.frames.last(), Some(Frame::Repeated(_)));
+
+        match (style, self.key.take()) {
+            (SeqStyle::Repeated, Some(key)) if !nested => {
+                self.frames.push(Frame::Repeated(key));
+                Ok(())
+            }
+            (_, key) => {
+                self.key = key;
+                self.begin_block()
+            }
+        }
+    }
+
+    fn end_block(&mut self) -> Result<()> {
+        match self.frames.pop() {
+            // The elements wrote their own lines, so the entry must not be ended again.
+            Some(Frame::Repeated(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => {
+                // The line of the last entry ends inside the block.
+                self.write("")?;
+                self.write_indent()?;
+                self.write("}")
+            }
+        }
+    }
 
-use crate::error::{Error, Result};
+    /// Ends a map, which for the root map is the end of the document.
+    fn end_map(&mut self) -> Result<()> {
+        match self.frames.is_empty() {
+            true => {
+                match self.config.final_newline {
+                    true => self.write("")?,
+                    false => self.newline = false,
+                }
+                Ok(())
+            }
+            false => self.end_block(),
+        }
+    }
+
+    /// Opens the single-entry block of an enum variant, leaving the variant as the pending key.
+    fn begin_variant(&mut self, variant: &str) -> Result<()> {
+        self.begin_map()?;
+        self.key = Some(quote(variant));
+        Ok(())
+    }
+
+    /// Sets the key of the next element of a sequence.
+    fn begin_element(&mut self) -> Result<()> {
+        self.key = match self.frames.last_mut() {
+            Some(Frame::Block(index)) => Some(format!("\"{index}\"")),
+            Some(Frame::Repeated(key)) => Some(key.clone()),
+            _ => None,
+        };
+        Ok(())
+    }
+
+    /// Ends an element of a sequence. Elements that wrote nothing, such as `None`, are left
+    /// out without using up an index, so that the indices stay contiguous.
+    fn end_element(&mut self) -> Result<()> {
+        if self.key.is_none() {
+            if let Some(Frame::Block(index)) = self.frames.last_mut() {
+                *index += 1;
+            }
+        }
+        self.end_entry()
+    }
+}
+
+enum Frame {
+    /// A `{ ... }` block, holding the index of its next element if it is a sequence.
+    Block(usize),
+    /// A sequence written as one entry per element, all under this key.
+    Repeated(String),
+    /// A [`crate::Conditional`], whose fields make up a single entry, holding its condition
+    /// until the value is written.
+    Conditional(Option<String>),
+    /// The condition of a [`crate::Conditional`] being serialized.
+    Condition,
+}
 
-pub struct Serializer {
-    output: String,
-    indexes: Vec<usize>,
+fn quote(v: &str) -> String {
+    format!("\"{}\"", escape(v))
 }
 
 pub fn to_string<T>(value: &T) -> Result<String>
 where
-    T: Serialize,
+    T: ?Sized + Serialize,
 {
-    let mut serializer = Serializer {
-        output: String::new(),
-        indexes: Vec::new(),
-    };
-    value.serialize(&mut serializer)?;
-    Ok(serializer.output)
+    String::from_utf8(to_vec(value)?).map_err(|_| ErrorKind::InvalidUtf8.into())
+}
+
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut output = Vec::new();
+    to_writer(&mut output, value)?;
+    Ok(output)
+}
+
+/// Serializes `value` into `writer` without buffering the document in memory.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    value.serialize(&mut Serializer::new(writer))
+}
+
+/// Serializes `value` as a string laid out as described by `config`.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use better_vdf::PrettyConfig;
+///
+/// let value = BTreeMap::from([("name", "hud"), ("version", "2")]);
+///
+/// assert_eq!(
+///     better_vdf::to_string_pretty(&value, &PrettyConfig::compact()).unwrap(),
+///     r#""name" "hud" "version" "2""#,
+/// );
+/// assert_eq!(
+///     better_vdf::to_string_pretty(&value, &PrettyConfig::new().align_values(12)).unwrap(),
+///     "\"name\"      \"hud\"\n\"version\"   \"2\"\n",
+/// );
+/// ```
+pub fn to_string_pretty<T>(value: &T, config: &PrettyConfig) -> Result<String>
+where
+    T: ?Sized + Serialize,
+{
+    let mut output = Vec::new();
+    to_writer_pretty(&mut output, value, config)?;
+    String::from_utf8(output).map_err(|_| ErrorKind::InvalidUtf8.into())
+}
+
+/// Serializes `value` into `writer`, laid out as described by `config`.
+pub fn to_writer_pretty<W, T>(writer: W, value: &T, config: &PrettyConfig) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    value.serialize(&mut Serializer::pretty(writer, config.clone()))
+}
+
+/// Serializes `value` into `writer` in the given encoding, starting with its byte order mark.
+///
+/// Use [`Encoding::Utf16Le`] to write localization files the way Steam expects them.
+pub fn to_writer_with_encoding<W, T>(mut writer: W, value: &T, encoding: Encoding) -> Result<()>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+{
+    match encoding {
+        Encoding::Utf8 | Encoding::Utf8Bom => {
+            writer
+                .write_all(encoding.bom())
+                .map_err(|err| Error::from(ErrorKind::Io(err)))?;
+            to_writer(writer, value)
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => writer
+            .write_all(&encoding.encode(&to_string(value)?))
+            .map_err(|err| Error::from(ErrorKind::Io(err))),
+    }
+}
+
+/// Escapes quotes, backslashes, newlines and tabs, borrowing `v` if nothing needs escaping.
+pub(crate) fn escape(v: &str) -> Cow<'_, str> {
+    if !v.contains(['"', '\\', '\n', '\t']) {
+        return Cow::Borrowed(v);
+    }
+
+    let mut output = String::with_capacity(v.len() + 2);
+    for ch in v.chars() {
+        match ch {
+            '"' => output += "\\\"",
+            '\\' => output += "\\\\",
+            '\n' => output += "\\n",
+            '\t' => output += "\\t",
+            _ => output.push(ch),
+        }
+    }
+    Cow::Owned(output)
+}
+
+/// Writes `v` as a quoted string, escaping quotes, backslashes, newlines and tabs.
+pub(crate) fn write_escaped(output: &mut String, v: &str) {
+    output.push('"');
+    *output += &escape(v);
+    output.push('"');
 }
 
-impl<'a> serde::ser::Serializer for &'a mut Serializer {
+impl<W> serde::ser::Serializer for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -32,8 +546,7 @@ impl<'a> serde::ser::Serializer for &'a mut Serializer {
     type SerializeStructVariant = Self;
 
     fn serialize_bool(self, v: bool) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.output += if v { "\"1\"" } else { "\"0\"" };
-        Ok(())
+        self.write_str(if v { "1" } else { "0" })
     }
 
     fn serialize_i8(self, v: i8) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
@@ -49,8 +562,7 @@ impl<'a> serde::ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_i64(self, v: i64) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.output += &format!("\"{v}\"");
-        Ok(())
+        self.write_str(&v.to_string())
     }
 
     fn serialize_u8(self, v: u8) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
@@ -66,53 +578,58 @@ impl<'a> serde::ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_u64(self, v: u64) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.output += &format!("\"{v}\"");
-        Ok(())
+        self.write_str(&v.to_string())
     }
 
     fn serialize_f32(self, v: f32) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.serialize_f64(f64::from(v))
+        self.write_str(&self.config.float_format.format(v))
     }
 
     fn serialize_f64(self, v: f64) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.output += &format!("\"{v}\"");
-        Ok(())
+        self.write_str(&self.config.float_format.format(v))
     }
 
     fn serialize_char(self, v: char) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.output += &format!("\"{v}\"");
-        Ok(())
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, v: &str) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.output += &format!("\"{v}\"");
-        Ok(())
+        match self.frames.last() {
+            Some(Frame::Condition) => {
+                let at = self.frames.len() - 2;
+                self.frames[at] = Frame::Conditional(Some(v.to_owned()));
+                Ok(())
+            }
+            _ => self.write_str(v),
+        }
     }
 
     fn serialize_bytes(self, _v: &[u8]) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        Err(Error::UnsupportedType)
+        Err(ErrorKind::UnsupportedType.into())
     }
 
+    /// Writes nothing, so that the pending key is dropped along with the entry. A `None`
+    /// element of a sequence is left out the same way.
     fn serialize_none(self) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
         Ok(())
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> std::prelude::v1::Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> std::prelude::v1::Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(self)
     }
 
     fn serialize_unit(self) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        Err(Error::UnsupportedType)
+        Err(ErrorKind::UnsupportedType.into())
     }
 
     fn serialize_unit_struct(
         self,
         _name: &'static str,
     ) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        Err(Error::UnsupportedType)
+        Err(ErrorKind::UnsupportedType.into())
     }
 
     fn serialize_unit_variant(
@@ -124,36 +641,46 @@ impl<'a> serde::ser::Serializer for &'a mut Serializer {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> std::prelude::v1::Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        self.next_seq = match name {
+            REPEATED => Some(SeqStyle::Repeated),
+            INDEXED => Some(SeqStyle::Indexed),
+            _ => return value.serialize(self),
+        };
+
+        value.serialize(&mut *self)?;
+        self.next_seq = None;
+        Ok(())
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> std::prelude::v1::Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        self.begin_variant(variant)?;
+        value.serialize(&mut *self)?;
+        self.end_entry()?;
+        self.end_map()
     }
 
     fn serialize_seq(
         self,
         _len: Option<usize>,
     ) -> std::prelude::v1::Result<Self::SerializeSeq, Self::Error> {
-        self.output += "{";
-        self.indexes.push(0);
+        self.begin_seq()?;
         Ok(self)
     }
 
@@ -176,26 +703,32 @@ impl<'a> serde::ser::Serializer for &'a mut Serializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> std::prelude::v1::Result<Self::SerializeTupleVariant, Self::Error> {
-        Err(Error::UnsupportedType)
+        self.begin_variant(variant)?;
+        self.begin_block()?;
+        Ok(self)
     }
 
     fn serialize_map(
         self,
         _len: Option<usize>,
     ) -> std::prelude::v1::Result<Self::SerializeMap, Self::Error> {
-        self.output += "{";
-        self.indexes.push(0);
+        self.begin_map()?;
         Ok(self)
     }
 
     fn serialize_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
     ) -> std::prelude::v1::Result<Self::SerializeStruct, Self::Error> {
+        if name == CONDITIONAL {
+            self.frames.push(Frame::Conditional(None));
+            return Ok(self);
+        }
+
         self.serialize_map(Some(len))
     }
 
@@ -203,14 +736,19 @@ impl<'a> serde::ser::Serializer for &'a mut Serializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> std::prelude::v1::Result<Self::SerializeStructVariant, Self::Error> {
-        Err(Error::UnsupportedType)
+        self.begin_variant(variant)?;
+        self.begin_block()?;
+        Ok(self)
     }
 }
 
-impl<'a> serde::ser::SerializeSeq for &'a mut Serializer {
+impl<W> serde::ser::SerializeSeq for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -218,31 +756,20 @@ impl<'a> serde::ser::SerializeSeq for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-
-        self.indexes
-            .last()
-            .copied()
-            .unwrap()
-            .serialize(&mut **self)?;
-
-        self.output += "\t\t";
-
-        value.serialize(&mut **self)
+        self.begin_element()?;
+        value.serialize(&mut **self)?;
+        self.end_element()
     }
 
     fn end(self) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.indexes.pop();
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-        self.output += "}";
-
-        Ok(())
+        self.end_block()
     }
 }
 
-impl<'a> serde::ser::SerializeTuple for &'a mut Serializer {
+impl<W> serde::ser::SerializeTuple for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -250,31 +777,20 @@ impl<'a> serde::ser::SerializeTuple for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-
-        self.indexes
-            .last()
-            .copied()
-            .unwrap()
-            .serialize(&mut **self)?;
-
-        self.output += "\t\t";
-
-        value.serialize(&mut **self)
+        self.begin_element()?;
+        value.serialize(&mut **self)?;
+        self.end_element()
     }
 
     fn end(self) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.indexes.pop();
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-        self.output += "}";
-
-        Ok(())
+        self.end_block()
     }
 }
 
-impl<'a> serde::ser::SerializeTupleStruct for &'a mut Serializer {
+impl<W> serde::ser::SerializeTupleStruct for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -282,47 +798,43 @@ impl<'a> serde::ser::SerializeTupleStruct for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-
-        self.indexes
-            .last()
-            .copied()
-            .unwrap()
-            .serialize(&mut **self)?;
-
-        self.output += "\t\t";
-
-        value.serialize(&mut **self)
+        self.begin_element()?;
+        value.serialize(&mut **self)?;
+        self.end_element()
     }
 
     fn end(self) -> std::prelude::v1::Result<Self::Ok, Self::Error> {
-        self.indexes.pop();
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-        self.output += "}";
-
-        Ok(())
+        self.end_block()
     }
 }
 
-impl<'a> serde::ser::SerializeTupleVariant for &'a mut Serializer {
+impl<W> serde::ser::SerializeTupleVariant for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        self.begin_element()?;
+        value.serialize(&mut **self)?;
+        self.end_element()
     }
 
     fn end(self) -> Result<()> {
-        Err(Error::UnsupportedType)
+        self.end_block()?;
+        self.end_entry()?;
+        self.end_map()
     }
 }
 
-impl<'a> serde::ser::SerializeMap for &'a mut Serializer {
+impl<W> serde::ser::SerializeMap for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -330,30 +842,29 @@ impl<'a> serde::ser::SerializeMap for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-
-        key.serialize(&mut **self)
+        self.key = Some(key.serialize(KeySerializer {
+            float_format: self.config.float_format,
+        })?);
+        Ok(())
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        self.output += "\t\t";
-        value.serialize(&mut **self)
+        value.serialize(&mut **self)?;
+        self.end_entry()
     }
 
     fn end(self) -> Result<()> {
-        self.indexes.pop();
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-        self.output += "}";
-        Ok(())
+        self.end_map()
     }
 }
 
-impl<'a> serde::ser::SerializeStruct for &'a mut Serializer {
+impl<W> serde::ser::SerializeStruct for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
@@ -361,34 +872,224 @@ impl<'a> serde::ser::SerializeStruct for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-        key.serialize(&mut **self)?;
-        self.output += "\t\t";
-        value.serialize(&mut **self)
+        // The condition of a `Conditional` is held in its frame until its value is written
+        // under the pending key.
+        if let Some(Frame::Conditional(_)) = self.frames.last() {
+            if key == "condition" {
+                self.frames.push(Frame::Condition);
+                value.serialize(&mut **self)?;
+                self.frames.pop();
+                return Ok(());
+            }
+            return value.serialize(&mut **self);
+        }
+
+        self.key = Some(quote(key));
+        value.serialize(&mut **self)?;
+        self.end_entry()
     }
 
     fn end(self) -> Result<()> {
-        self.indexes.pop();
-        self.output += "\n";
-        self.output += &"\t".repeat(self.indexes.len());
-        self.output += "}";
-        Ok(())
+        if let Some(Frame::Conditional(_)) = self.frames.last() {
+            self.frames.pop();
+            return Ok(());
+        }
+
+        self.end_map()
     }
 }
 
-impl<'a> serde::ser::SerializeStructVariant for &'a mut Serializer {
+impl<W> serde::ser::SerializeStructVariant for &mut Serializer<W>
+where
+    W: io::Write,
+{
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        Err(Error::UnsupportedType)
+        serde::ser::SerializeStruct::serialize_field(self, key, value)
     }
 
     fn end(self) -> Result<()> {
-        Err(Error::UnsupportedType)
+        self.end_block()?;
+        self.end_entry()?;
+        self.end_map()
+    }
+}
+
+/// Serializes the key of a map entry as a quoted string. Strings, numbers, booleans, chars
+/// and unit variants can be keys, anything else fails with [`ErrorKind::KeyMustBeAString`].
+#[derive(Clone, Copy)]
+struct KeySerializer {
+    float_format: FloatFormat,
+}
+
+impl KeySerializer {
+    fn unsupported<T>(&self) -> Result<T> {
+        Err(ErrorKind::KeyMustBeAString.into())
+    }
+}
+
+impl serde::ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<String> {
+        self.serialize_str(if v { "1" } else { "0" })
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String> {
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String> {
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<String> {
+        self.serialize_str(&self.float_format.format(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<String> {
+        self.serialize_str(&self.float_format.format(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<String> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        Ok(quote(v))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String> {
+        self.unsupported()
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        self.unsupported()
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.unsupported()
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.unsupported()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.unsupported()
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        self.unsupported()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.unsupported()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.unsupported()
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.unsupported()
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        self.unsupported()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.unsupported()
     }
 }
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use better_vdf::{ErrorKind, Position};
+
+type Entries = BTreeMap<String, String>;
+
+#[test]
+fn line_comments() {
+    let text = "// header\n\"a\" // after a key\n\"1\" // after a value\n//\"b\" \"2\"\n\"c\" \"//3\" // end";
+    let entries: Entries = better_vdf::from_str(text).unwrap();
+
+    assert_eq!(
+        entries,
+        Entries::from([
+            ("a".to_owned(), "1".to_owned()),
+            ("c".to_owned(), "//3".to_owned()),
+        ])
+    );
+}
+
+#[test]
+fn block_comments() {
+    let text = "/* header\n \"x\" \"0\" */ \"a\" /* inline */ \"1\" \"b\" { /**/ \"c\" \"2\" }";
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Doc {
+        a: String,
+        b: Entries,
+    }
+
+    let doc: Doc = better_vdf::options()
+        .block_comments(true)
+        .from_str(text)
+        .unwrap();
+    assert_eq!(doc.a, "1");
+    assert_eq!(doc.b["c"], "2");
+
+    assert!(better_vdf::from_str::<Doc>(text).is_err());
+}
+
+#[test]
+fn unterminated_block_comments() {
+    for text in [
+        "\"a\" \"1\" /* never closed\n\"b\" \"2\"",
+        "\"a\" /* never closed",
+        "\"a\" { \"b\" \"1\" /* never closed }",
+    ] {
+        let err = better_vdf::options()
+            .block_comments(true)
+            .from_str::<BTreeMap<String, better_vdf::Value>>(text)
+            .unwrap_err();
+
+        assert!(
+            matches!(err.kind(), ErrorKind::UnterminatedComment),
+            "{text}: {err}"
+        );
+        let offset = text.find("/*").unwrap();
+        assert_eq!(
+            err.position(),
+            Some(Position {
+                offset,
+                line: 1,
+                column: offset + 1,
+            }),
+            "{text}"
+        );
+    }
+
+    let err = better_vdf::options()
+        .lenient()
+        .from_str::<Entries>("\"a\" \"1\"\n/* trailing")
+        .unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::UnterminatedComment),
+        "{err}"
+    );
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use better_vdf::{Conditional, ErrorKind};
+use serde::{Deserialize, Serialize};
+
+fn read(text: &str, symbols: &[&str]) -> better_vdf::Result<BTreeMap<String, String>> {
+    symbols
+        .iter()
+        .fold(better_vdf::options(), |options, symbol| {
+            options.define(*symbol)
+        })
+        .from_str(text)
+}
+
+fn enabled(condition: &str) -> bool {
+    let text = format!("\"key\" \"value\" [{condition}]");
+    let entries = read(&text, &["WIN32", "POSIX"]).unwrap_or_else(|err| panic!("{err}"));
+    entries.contains_key("key")
+}
+
+#[test]
+fn evaluation() {
+    let cases = [
+        ("$WIN32", true),
+        ("$X360", false),
+        ("!$WIN32", false),
+        ("!$X360", true),
+        ("$win32", true),
+        ("WIN32", true),
+        ("$WIN32 && !$X360", true),
+        ("$WIN32 && $X360", false),
+        ("$X360 || $POSIX", true),
+        ("$X360 || $OSX", false),
+        ("!($WIN32 && $X360)", true),
+        ("$X360 && $WIN32 || $POSIX", true),
+        ("!!$WIN32", true),
+        ("( ( $WIN32 ) )", true),
+    ];
+
+    for (condition, expected) in cases {
+        assert_eq!(enabled(condition), expected, "{condition}");
+    }
+}
+
+#[test]
+fn defined_symbols_pick_entries() {
+    let text = r#"
+        "font" "Tahoma" [$WIN32]
+        "font" "Helvetica" [$OSX]
+        "size" "12"
+        "Linux" [$LINUX] { "font" "DejaVu Sans" }
+    "#;
+
+    let windows = read(text, &["WIN32"]).unwrap();
+    assert_eq!(windows["font"], "Tahoma");
+    assert_eq!(windows["size"], "12");
+
+    let mac = read(text, &["OSX"]).unwrap();
+    assert_eq!(mac["font"], "Helvetica");
+
+    #[derive(Deserialize)]
+    struct Fonts {
+        #[serde(rename = "Linux")]
+        linux: Option<BTreeMap<String, String>>,
+    }
+
+    let fonts: Fonts = better_vdf::options().from_str(text).unwrap();
+    assert!(fonts.linux.is_none());
+
+    let fonts: Fonts = better_vdf::options()
+        .define("LINUX")
+        .from_str(text)
+        .unwrap();
+    assert_eq!(fonts.linux.unwrap()["font"], "DejaVu Sans");
+}
+
+#[test]
+fn syntax_errors() {
+    for condition in ["", "$", "$WIN32 &&", "($WIN32", "$WIN32)", "$WIN32 $X360"] {
+        let text = format!("\"key\" \"value\" [{condition}]");
+        let err = read(&text, &[]).unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::ConditionSyntax(_)),
+            "{condition}: {err}"
+        );
+    }
+}
+
+#[test]
+fn deep_nesting() {
+    let negations = format!("{}$WIN32", "!".repeat(200_000));
+    assert!(enabled(&negations));
+
+    let groups = format!("{}$WIN32{}", "(".repeat(200_000), ")".repeat(200_000));
+    let err = read(&format!("\"key\" \"value\" [{groups}]"), &[]).unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::RecursionLimitExceeded),
+        "{err}"
+    );
+}
+
+#[test]
+fn preserved_conditionals() {
+    let text = r#""font" "Tahoma" [$WIN32] "size" "12" "color" "red" [!$X360]"#;
+
+    let entries: BTreeMap<String, Conditional<String>> = better_vdf::options()
+        .preserve_conditionals(true)
+        .from_str(text)
+        .unwrap();
+
+    assert_eq!(entries["font"].value, "Tahoma");
+    assert_eq!(entries["font"].condition.as_deref(), Some("$WIN32"));
+    assert_eq!(entries["size"].condition, None);
+    assert_eq!(entries["color"].condition.as_deref(), Some("!$X360"));
+}
+
+#[test]
+fn conditional_round_trip() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Settings {
+        font: Conditional<String>,
+        size: Conditional<String>,
+        colors: Conditional<BTreeMap<String, String>>,
+    }
+
+    let settings = Settings {
+        font: Conditional {
+            value: "Tahoma".to_owned(),
+            condition: Some("$WIN32".to_owned()),
+        },
+        size: Conditional {
+            value: "12".to_owned(),
+            condition: None,
+        },
+        colors: Conditional {
+            value: BTreeMap::from([("fg".to_owned(), "white".to_owned())]),
+            condition: Some("!$X360".to_owned()),
+        },
+    };
+
+    let text = better_vdf::to_string(&settings).unwrap();
+    assert_eq!(
+        text,
+        "\"font\"\t\t\"Tahoma\" [$WIN32]\n\"size\"\t\t\"12\"\n\
+         \"colors\" [!$X360]\t\t{\n\t\"fg\"\t\t\"white\"\n}\n"
+    );
+
+    let read: Settings = better_vdf::options()
+        .preserve_conditionals(true)
+        .from_str(&text)
+        .unwrap();
+    assert_eq!(read, settings);
+}
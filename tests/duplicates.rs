@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use better_vdf::{Deserializer, DuplicateKeys, ErrorKind, Value};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+const VDF: &str = r#"
+"name"      "first"
+"count"     "1"
+"name"      "second"
+"name"      "third"
+"#;
+
+fn parse<T>(policy: DuplicateKeys, input: &str) -> better_vdf::Result<T>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(&mut Deserializer::from_str(input).duplicate_keys(policy))
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Single {
+    name: String,
+    count: u32,
+}
+
+#[test]
+fn keep_passes_every_entry_on() {
+    let err = parse::<Single>(DuplicateKeys::Keep, VDF).unwrap_err();
+    assert!(err.to_string().contains("duplicate field"), "{err}");
+
+    let map: HashMap<String, String> = parse(DuplicateKeys::Keep, VDF).unwrap();
+    assert_eq!(map["name"], "third");
+
+    let value: Value = parse(DuplicateKeys::Keep, VDF).unwrap();
+    assert_eq!(value.as_object().unwrap().get_all("name").count(), 3);
+}
+
+#[test]
+fn error_points_at_the_duplicate() {
+    let err = parse::<HashMap<String, String>>(DuplicateKeys::Error, VDF).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::DuplicateKey(key) if key == "name"));
+    assert_eq!(err.position().unwrap().line, 4);
+}
+
+#[test]
+fn first_and_last_wins() {
+    let first: Single = parse(DuplicateKeys::FirstWins, VDF).unwrap();
+    assert_eq!(first.name, "first");
+
+    let last: Single = parse(DuplicateKeys::LastWins, VDF).unwrap();
+    assert_eq!(last.name, "third");
+    assert_eq!(last.count, 1);
+}
+
+#[test]
+fn policies_apply_to_nested_blocks() {
+    let input = r#""outer" { "a" "1" "inner" { "b" "2" "b" "3" } "a" "4" }"#;
+
+    let map: HashMap<String, HashMap<String, Value>> =
+        parse(DuplicateKeys::LastWins, input).unwrap();
+
+    assert_eq!(map["outer"]["a"].as_str(), Some("4"));
+    assert_eq!(map["outer"]["inner"]["b"].as_str(), Some("3"));
+}
+
+#[test]
+fn collect_gathers_repeated_keys() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Collected {
+        name: Vec<String>,
+        count: u32,
+    }
+
+    let collected: Collected = parse(DuplicateKeys::Collect, VDF).unwrap();
+    assert_eq!(collected.name, ["first", "second", "third"]);
+    assert_eq!(collected.count, 1);
+
+    let map: HashMap<String, Vec<String>> =
+        parse(DuplicateKeys::Collect, r#""a" "1" "b" "2" "a" "3" "b" "4""#).unwrap();
+    assert_eq!(map["a"], ["1", "3"]);
+    assert_eq!(map["b"], ["2", "4"]);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Items {
+    #[serde(with = "better_vdf::repeated", default)]
+    item: Vec<Item>,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Item {
+    id: u32,
+}
+
+#[test]
+fn repeated_fields() {
+    let input = r#"
+"item" { "id" "1" }
+"name" "items"
+"item" { "id" "2" }
+"item" [$NEVER] { "id" "3" }
+"#;
+
+    for policy in [
+        DuplicateKeys::Keep,
+        DuplicateKeys::Error,
+        DuplicateKeys::Collect,
+    ] {
+        let items: Items = parse(policy, input).unwrap();
+        assert_eq!(items.item, [Item { id: 1 }, Item { id: 2 }]);
+        assert_eq!(items.name, "items");
+    }
+
+    let single: Items = better_vdf::from_str(r#""name" "x" "item" { "id" "5" }"#).unwrap();
+    assert_eq!(single.item, [Item { id: 5 }]);
+
+    let none: Items = better_vdf::from_str(r#""name" "x""#).unwrap();
+    assert!(none.item.is_empty());
+}
+
+#[test]
+fn repeated_fields_round_trip() {
+    let items = Items {
+        item: vec![Item { id: 1 }, Item { id: 2 }],
+        name: "items".to_owned(),
+    };
+
+    let text = better_vdf::to_string(&items).unwrap();
+    assert_eq!(
+        text,
+        "\"item\"\t\t{\n\t\"id\"\t\t\"1\"\n}\n\"item\"\t\t{\n\t\"id\"\t\t\"2\"\n}\n\"name\"\t\t\"items\"\n"
+    );
+    assert_eq!(better_vdf::from_str::<Items>(&text).unwrap(), items);
+
+    let empty = Items {
+        item: Vec::new(),
+        name: "empty".to_owned(),
+    };
+    let text = better_vdf::to_string(&empty).unwrap();
+    assert_eq!(text, "\"name\"\t\t\"empty\"\n");
+    assert_eq!(better_vdf::from_str::<Items>(&text).unwrap(), empty);
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use better_vdf::{Encoding, ErrorKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Language {
+    language: String,
+    tokens: BTreeMap<String, String>,
+}
+
+const TEXT: &str =
+    "\"Language\"\t\t\"english\"\n\"Tokens\"\t\t{\n\t\"TF_Hat\"\t\t\"Chapeau ✓\"\n}\n";
+
+fn language() -> Language {
+    Language {
+        language: "english".to_owned(),
+        tokens: BTreeMap::from([("TF_Hat".to_owned(), "Chapeau ✓".to_owned())]),
+    }
+}
+
+#[test]
+fn detection() {
+    for encoding in [Encoding::Utf8Bom, Encoding::Utf16Le, Encoding::Utf16Be] {
+        assert_eq!(Encoding::detect(&encoding.encode(TEXT)), encoding);
+    }
+    assert_eq!(Encoding::detect(TEXT.as_bytes()), Encoding::Utf8);
+}
+
+#[test]
+fn byte_order_marks_are_stripped() {
+    for encoding in [
+        Encoding::Utf8,
+        Encoding::Utf8Bom,
+        Encoding::Utf16Le,
+        Encoding::Utf16Be,
+    ] {
+        let bytes = encoding.encode(TEXT);
+        assert_eq!(encoding.decode(&bytes).unwrap(), TEXT, "{encoding:?}");
+    }
+}
+
+#[test]
+fn utf16_readers() {
+    for encoding in [Encoding::Utf16Le, Encoding::Utf16Be] {
+        let bytes = encoding.encode(TEXT);
+
+        let from_reader: Language = better_vdf::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(from_reader, language(), "{encoding:?}");
+
+        let lenient: Language = better_vdf::options()
+            .lenient()
+            .from_reader(bytes.as_slice())
+            .unwrap();
+        assert_eq!(lenient, language(), "{encoding:?}");
+
+        let err = better_vdf::from_slice::<Language>(&bytes).unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::UnsupportedEncoding(x) if *x == encoding),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn utf8_slices_borrow() {
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(rename = "Language")]
+        language: &'a str,
+    }
+
+    let text = "\"Language\"\t\t\"english\"\n";
+    for encoding in [Encoding::Utf8, Encoding::Utf8Bom] {
+        let bytes = encoding.encode(text);
+        let borrowed: Borrowed = better_vdf::from_slice(&bytes).unwrap();
+        assert_eq!(borrowed.language, "english", "{encoding:?}");
+    }
+}
+
+#[test]
+fn encode_round_trip() {
+    for encoding in [
+        Encoding::Utf8,
+        Encoding::Utf8Bom,
+        Encoding::Utf16Le,
+        Encoding::Utf16Be,
+    ] {
+        let mut bytes = Vec::new();
+        better_vdf::to_writer_with_encoding(&mut bytes, &language(), encoding).unwrap();
+
+        assert_eq!(bytes, encoding.encode(TEXT), "{encoding:?}");
+        assert_eq!(
+            better_vdf::from_reader::<_, Language>(bytes.as_slice()).unwrap(),
+            language()
+        );
+    }
+}
+
+#[test]
+fn invalid_utf16() {
+    let mut odd = Encoding::Utf16Le.encode("\"a\" \"b\"");
+    odd.push(b'x');
+
+    let mut unpaired = Encoding::Utf16Le.encode("\"a\" \"");
+    unpaired.extend(0xD800u16.to_le_bytes());
+
+    for bytes in [odd, unpaired] {
+        let err =
+            better_vdf::from_reader::<_, BTreeMap<String, String>>(bytes.as_slice()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidUtf16), "{err}");
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use better_vdf::{ErrorKind, FloatFormat, PrettyConfig};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Floats {
+    double: f64,
+    single: f32,
+}
+
+fn write(format: FloatFormat, double: f64, single: f32) -> String {
+    better_vdf::to_string_pretty(
+        &Floats { double, single },
+        &PrettyConfig::compact().float_format(format),
+    )
+    .unwrap()
+}
+
+#[test]
+fn doubles_keep_their_precision() {
+    let floats = Floats {
+        double: 0.1 + 0.2,
+        single: 12.9,
+    };
+
+    let text = better_vdf::to_string(&floats).unwrap();
+    assert_eq!(
+        text,
+        "\"double\"\t\t\"0.30000000000000004\"\n\"single\"\t\t\"12.9\"\n"
+    );
+    assert_eq!(better_vdf::from_str::<Floats>(&text).unwrap(), floats);
+}
+
+#[test]
+fn formats() {
+    assert_eq!(
+        write(FloatFormat::Display, 1e-7, 1000.0),
+        r#""double" "0.0000001" "single" "1000""#
+    );
+    assert_eq!(
+        write(FloatFormat::Shortest, 1e-7, 1000.0),
+        r#""double" "1e-7" "single" "1e3""#
+    );
+    assert_eq!(
+        write(FloatFormat::Fixed(2), 1.5, -0.125),
+        r#""double" "1.50" "single" "-0.12""#
+    );
+    assert_eq!(
+        write(FloatFormat::Valve, 1.0, 0.25),
+        r#""double" "1.000000" "single" "0.250000""#
+    );
+    assert_eq!(
+        write(FloatFormat::Valve, f64::NAN, f32::NEG_INFINITY),
+        r#""double" "nan" "single" "-inf""#
+    );
+}
+
+#[test]
+fn source_spellings() {
+    let cases = [
+        ("1.", 1.0),
+        (".5", 0.5),
+        ("-.5", -0.5),
+        ("1e5", 100000.0),
+        ("1.000000", 1.0),
+        ("nan", f64::NAN),
+        ("1.#QNAN", f64::NAN),
+        ("-1.#IND", f64::NAN),
+        ("-1.#INF", f64::NEG_INFINITY),
+    ];
+
+    for (text, expected) in cases {
+        let floats: Floats =
+            better_vdf::from_str(&format!("\"double\" \"{text}\" \"single\" \"{text}\"")).unwrap();
+
+        for found in [floats.double, f64::from(floats.single)] {
+            assert!(
+                found == expected || found.is_nan() && expected.is_nan(),
+                "{text} read as {found}"
+            );
+        }
+    }
+
+    let err = better_vdf::from_str::<Floats>(r#""double" "1.5x" "single" "0""#).unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::ExpectedFloat(found) if found == "1.5x"),
+        "{err}"
+    );
+}
+
+#[test]
+fn buffered_floats() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(tag = "type")]
+    enum Shape {
+        Circle {
+            #[serde(with = "better_vdf::number")]
+            radius: f64,
+        },
+    }
+
+    let shapes = BTreeMap::from([("0".to_owned(), Shape::Circle { radius: 1e-7 })]);
+    let text = better_vdf::to_string(&shapes).unwrap();
+
+    assert!(text.contains("\"0.0000001\""), "{text}");
+    assert_eq!(
+        better_vdf::from_str::<BTreeMap<String, Shape>>(&text).unwrap(),
+        shapes
+    );
+}
@@ -0,0 +1,169 @@
+use std::{collections::HashMap, fs, io};
+
+use better_vdf::{ErrorKind, FileResolver, KeyCase, MemoryResolver, Resolver, Value};
+use serde::Deserialize;
+
+#[test]
+fn file_resolver_stays_inside_its_root() {
+    let root = std::env::temp_dir().join(format!("better_vdf_include_{}", std::process::id()));
+    fs::create_dir_all(root.join("scripts")).unwrap();
+    fs::write(root.join("scripts/base.res"), r#""Hud" { "tall" "10" }"#).unwrap();
+
+    let resolver = FileResolver::new(&root);
+    assert!(resolver.resolve("scripts/base.res").is_ok());
+    assert!(resolver.resolve("scripts\\base.res").is_ok());
+    assert!(resolver.resolve("./scripts/base.res").is_ok());
+    assert!(resolver.resolve("scripts/../scripts/base.res").is_ok());
+
+    let outside = root.join("scripts/base.res").display().to_string();
+    for path in [
+        "../secret.txt",
+        "scripts/../../secret.txt",
+        "/etc/passwd",
+        &outside,
+    ] {
+        let err = resolver.resolve(path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{path}");
+    }
+
+    let err = better_vdf::from_str_with_resolver::<HashMap<String, String>, _>(
+        "#base \"../base.res\"",
+        &resolver,
+    )
+    .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Io(_)), "{err}");
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+fn hud_files() -> MemoryResolver {
+    let mut resolver = MemoryResolver::new();
+    resolver
+        .insert(
+            "base.res",
+            r#""Hud" { "tall" "10" "wide" "20" "Colors" { "fg" "white" "bg" "black" } }"#,
+        )
+        .insert(
+            "extra.res",
+            "\"Extra\" { \"tall\" \"5\" }\n#include \"more.res\"",
+        )
+        .insert("more.res", r#""More" { "tall" "1" }"#);
+    resolver
+}
+
+#[test]
+fn base_files_fill_in_missing_keys() {
+    let text = "#base \"base.res\"\n\"Hud\" { \"tall\" \"15\" \"Colors\" { \"fg\" \"red\" } }";
+    let hud: Value = better_vdf::from_str_with_resolver(text, hud_files()).unwrap();
+
+    assert_eq!(
+        better_vdf::to_string(&hud).unwrap(),
+        "\"Hud\"\t\t{\n\t\"tall\"\t\t\"15\"\n\t\"Colors\"\t\t{\n\t\t\"fg\"\t\t\"red\"\n\
+         \t\t\"bg\"\t\t\"black\"\n\t}\n\t\"wide\"\t\t\"20\"\n}\n"
+    );
+}
+
+#[test]
+fn included_files_are_appended() {
+    let text = "#include \"extra.res\"\n\"Hud\" { \"tall\" \"15\" }";
+    let hud: Value = better_vdf::from_str_with_resolver(text, hud_files()).unwrap();
+
+    let keys: Vec<&str> = hud.as_object().unwrap().keys().collect();
+    assert_eq!(keys, ["Hud", "Extra", "More"]);
+    assert_eq!(hud["More"]["tall"], Value::from("1"));
+}
+
+#[test]
+fn nested_directives_are_relative_to_their_file() {
+    let mut resolver = MemoryResolver::new();
+    resolver
+        .insert(
+            "ui/hud.res",
+            "#base \"base.res\"\n\"Hud\" { \"tall\" \"15\" }",
+        )
+        .insert(
+            "ui/base.res",
+            "#include \"../shared/colors.res\"\n\"Hud\" { \"wide\" \"20\" }",
+        )
+        .insert("shared/colors.res", r#""Colors" { "fg" "white" }"#);
+
+    let hud: Value =
+        better_vdf::from_str_with_resolver("#include \"ui/hud.res\"", &resolver).unwrap();
+    assert_eq!(hud["Hud"]["tall"], Value::from("15"));
+    assert_eq!(hud["Hud"]["wide"], Value::from("20"));
+    assert_eq!(hud["Colors"]["fg"], Value::from("white"));
+
+    let root = std::env::temp_dir().join(format!("better_vdf_nested_{}", std::process::id()));
+    fs::create_dir_all(root.join("ui")).unwrap();
+    fs::create_dir_all(root.join("shared")).unwrap();
+    for path in ["ui/hud.res", "ui/base.res", "shared/colors.res"] {
+        fs::write(root.join(path), resolver.resolve(path).unwrap()).unwrap();
+    }
+
+    let from_files: Value =
+        better_vdf::from_str_with_resolver("#include \"ui/hud.res\"", FileResolver::new(&root))
+            .unwrap();
+    assert_eq!(from_files, hud);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn missing_files() {
+    let err = better_vdf::from_str_with_resolver::<Value, _>(
+        "#base \"missing.res\"\n\"Hud\" { }",
+        hud_files(),
+    )
+    .unwrap_err();
+
+    match err.kind() {
+        ErrorKind::Io(err) => assert_eq!(err.kind(), io::ErrorKind::NotFound),
+        kind => panic!("{kind}"),
+    }
+}
+
+#[test]
+fn cycles() {
+    let mut resolver = MemoryResolver::new();
+    resolver
+        .insert("a.res", "#include \"b.res\"\n\"a\" \"1\"")
+        .insert("b.res", "#base \"a.res\"\n\"b\" \"2\"");
+
+    let err = better_vdf::from_str_with_resolver::<Value, _>("#include \"a.res\"", &resolver)
+        .unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::IncludeCycle(path) if path == "a.res"),
+        "{err}"
+    );
+}
+
+#[test]
+fn options_apply_to_the_merged_document() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Hud {
+        tall: u32,
+        wide: u32,
+    }
+
+    let text = "#base \"base.res\"\n\"hud\" { \"TALL\" \"15\" }";
+    let huds: HashMap<String, Hud> = better_vdf::options()
+        .key_case(KeyCase::Lowercase)
+        .from_str_with_resolver(text, hud_files())
+        .unwrap();
+    assert_eq!(huds["hud"], Hud { tall: 15, wide: 20 });
+
+    let err = better_vdf::from_str_with_resolver::<HashMap<String, Hud>, _>(
+        "\"hud\" { \"tall\" \"x\" \"wide\" \"1\" }",
+        hud_files(),
+    )
+    .unwrap_err();
+    assert_eq!(err.position().map(|x| x.offset), Some(15));
+
+    let err = better_vdf::from_str_with_resolver::<HashMap<String, Hud>, _>(
+        "#base \"base.res\"\n\"hud\" { \"tall\" \"x\" }",
+        hud_files(),
+    )
+    .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::ExpectedInteger(_)), "{err}");
+    assert_eq!(err.position(), None);
+}
@@ -0,0 +1,63 @@
+use better_vdf::{Deserializer, ErrorKind};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Lists {
+    list: Vec<String>,
+}
+
+fn lenient(input: &str) -> better_vdf::Result<Lists> {
+    Lists::deserialize(&mut Deserializer::from_str(input).lenient_indices(true))
+}
+
+#[test]
+fn strict_indices_report_expected_and_found() {
+    let err = better_vdf::from_str::<Lists>(r#""list" { "0" "a" "2" "b" }"#).unwrap_err();
+
+    assert!(matches!(
+        err.kind(),
+        ErrorKind::ArrayIndex {
+            expected: 1,
+            found: 2
+        }
+    ));
+    assert_eq!(err.position().unwrap().column, 18);
+}
+
+#[test]
+fn lenient_indices_accept_one_based_lists_and_gaps() {
+    let lists = lenient(r#""list" { "1" "a" "2" "b" "5" "c" }"#).unwrap();
+    assert_eq!(lists.list, ["a", "b", "c"]);
+}
+
+#[test]
+fn lenient_indices_sort_numeric_keys() {
+    let lists = lenient(r#""list" { "10" "c" "2" "b" "0" "a" }"#).unwrap();
+    assert_eq!(lists.list, ["a", "b", "c"]);
+}
+
+#[test]
+fn lenient_indices_keep_other_keys_in_order() {
+    let lists =
+        lenient(r#""list" { "second" "b" "1" "a" "third" "c" [$NEVER] "fourth" "d" }"#).unwrap();
+    // "third" is dropped because `[$NEVER]` is not defined.
+    assert_eq!(lists.list, ["a", "b", "d"]);
+}
+
+#[test]
+fn lenient_indices_read_nested_sequences() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Grid {
+        rows: Vec<Vec<u8>>,
+        after: String,
+    }
+
+    let grid = Grid::deserialize(
+        &mut Deserializer::from_str(r#""rows" { "1" { "9" "2" "3" "1" } "0" { } } "after" "ok""#)
+            .lenient_indices(true),
+    )
+    .unwrap();
+
+    assert_eq!(grid.rows, [vec![], vec![1, 2]]);
+    assert_eq!(grid.after, "ok");
+}
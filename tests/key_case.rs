@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use better_vdf::{Deserializer, DuplicateKeys, KeyCase, Value};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct AppState {
+    appid: u32,
+    name: String,
+    #[serde(rename = "StateFlags")]
+    state_flags: u8,
+}
+
+const APP_STATE: &str = r#""AppID" "440" "Name" "Team Fortress 2" "stateflags" "4""#;
+
+#[test]
+fn exact_by_default() {
+    assert!(better_vdf::from_str::<AppState>(APP_STATE).is_err());
+}
+
+#[test]
+fn ignore_case() {
+    let state =
+        AppState::deserialize(&mut Deserializer::from_str(APP_STATE).key_case(KeyCase::IgnoreCase))
+            .unwrap();
+
+    assert_eq!(
+        state,
+        AppState {
+            appid: 440,
+            name: "Team Fortress 2".to_owned(),
+            state_flags: 4,
+        }
+    );
+}
+
+#[test]
+fn lowercase() {
+    let map = BTreeMap::<String, String>::deserialize(
+        &mut Deserializer::from_str(r#""Key" "a" "OTHER" { } "plain" "b""#)
+            .key_case(KeyCase::Lowercase),
+    );
+
+    assert!(map.is_err());
+
+    let map = BTreeMap::<String, String>::deserialize(
+        &mut Deserializer::from_str(r#""Key" "a" "OTHER" "c" "plain" "b""#)
+            .key_case(KeyCase::Lowercase),
+    )
+    .unwrap();
+
+    assert_eq!(map.keys().collect::<Vec<_>>(), ["key", "other", "plain"]);
+}
+
+#[test]
+fn variants() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Mode {
+        Casual,
+        Ranked(u32),
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Modes {
+        modes: Vec<Mode>,
+    }
+
+    let modes = Modes::deserialize(
+        &mut Deserializer::from_str(r#""Modes" { "0" "casual" "1" { "RANKED" "3" } }"#)
+            .key_case(KeyCase::IgnoreCase),
+    )
+    .unwrap();
+
+    assert_eq!(modes.modes, [Mode::Casual, Mode::Ranked(3)]);
+}
+
+#[test]
+fn duplicates_ignore_case() {
+    let text = r#""appid" "1" "AppID" "2" "name" "x" "stateflags" "0""#;
+
+    let err = AppState::deserialize(
+        &mut Deserializer::from_str(text)
+            .key_case(KeyCase::IgnoreCase)
+            .duplicate_keys(DuplicateKeys::Error),
+    )
+    .unwrap_err();
+    assert!(matches!(err.kind(), better_vdf::ErrorKind::DuplicateKey(key) if key == "AppID"));
+
+    let state = AppState::deserialize(
+        &mut Deserializer::from_str(text)
+            .key_case(KeyCase::IgnoreCase)
+            .duplicate_keys(DuplicateKeys::LastWins),
+    )
+    .unwrap();
+    assert_eq!(state.appid, 2);
+}
+
+#[test]
+fn value_lookup() {
+    let value: Value = better_vdf::from_str(APP_STATE).unwrap();
+
+    assert!(value.get("appid").is_none());
+    assert_eq!(
+        value.get_ignore_case("APPID").and_then(Value::as_str),
+        Some("440")
+    );
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use better_vdf::{ErrorKind, Value};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Test {
+    test: TestData,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct TestData {
+    name: String,
+    enabled: bool,
+    list: Vec<TestObj>,
+    map: HashMap<u64, i64>,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct TestObj {
+    obj: String,
+    id: usize,
+    weight: f32,
+}
+
+const VALID: &str = r#"
+"test"
+{
+	"name"		"Better \"VDF\""
+	"enabled"		"1"
+	"list"
+	{
+		"0"
+		{
+			"obj"		"main_obj"
+			"id"		"19231"
+			"weight"		"12.9"
+		}
+	}
+	"map"
+	{
+		"228980"		"12318293" [$WIN32]
+	}
+}
+"#;
+
+/// Parses `input` as every supported target and checks that nothing panics, including
+/// formatting the errors.
+fn parse_all(input: &str) -> [bool; 3] {
+    let results = [
+        better_vdf::from_str::<Test>(input).map(drop),
+        better_vdf::from_str::<Value>(input).map(drop),
+        better_vdf::from_str::<HashMap<String, Value>>(input).map(drop),
+    ];
+
+    results.map(|result| match result {
+        Ok(()) => true,
+        Err(err) => {
+            assert!(!err.to_string().is_empty());
+            assert!(err.position().is_some(), "{err} has no position");
+            false
+        }
+    })
+}
+
+#[test]
+fn valid_document_parses() {
+    assert_eq!(parse_all(VALID), [true; 3]);
+}
+
+#[test]
+fn truncated_documents_fail() {
+    // A document of only whitespace is an empty root, so truncate after the first token starts.
+    let start = VALID.find('"').unwrap();
+    let end = VALID.rfind('}').unwrap();
+
+    for (len, _) in VALID
+        .char_indices()
+        .skip_while(|&(len, _)| len <= start)
+        .take_while(|&(len, _)| len <= end)
+    {
+        let input = &VALID[..len];
+        assert_eq!(parse_all(input), [false; 3], "{input:?} was accepted");
+    }
+}
+
+#[test]
+fn malformed_documents_fail() {
+    let inputs = [
+        "",
+        "}",
+        "{",
+        "\"test\"",
+        "\"test\" {",
+        "\"test\" }",
+        "\"test\" { } }",
+        "\"test\" { \"name\" }",
+        "\"test\" { \"name\" \"unterminated }",
+        "\"test\" { \"name\" \"x\" \"enabled\" \"yes\" }",
+        "\"test\" { \"list\" \"oops\" }",
+        "\"test\" { \"list\" { \"1\" { } } }",
+        "\"test\" { \"list\" { { } } }",
+        "\"test\" { \"map\" { \"x\" \"1\" } }",
+        "\"test\" { \"map\" { \"1\" \"1.5\" } }",
+        "\"test\" { \"name\" \"x\" [$WIN32 &&] }",
+        "\"test\" { \"name\" \"x\" [$WIN32 }",
+        "\"test\" { \"name\" \"x\\",
+        "\"test\" { \"name\" \"x\" } trailing",
+        "\"test\" { \"name\" \"x\" } \"other\"",
+    ];
+
+    for input in inputs {
+        assert!(!parse_all(input)[0], "{input:?} was accepted");
+    }
+}
+
+#[test]
+fn deep_nesting_fails() {
+    let input = format!("\"a\" {}{}", "{ \"a\" ".repeat(10_000), "}".repeat(10_000));
+
+    let err = better_vdf::from_str::<Value>(&input).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::RecursionLimitExceeded));
+}
+
+#[test]
+fn errors_describe_what_was_found() {
+    let err = better_vdf::from_str::<Test>("\"test\" { \"enabled\" \"yes\" }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected boolean, found \"yes\" at line 1 column 20"
+    );
+
+    let err = better_vdf::from_str::<Test>("\"test\" { \"list\" \"oops\" }").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected `{` of an array, found `\"` at line 1 column 17"
+    );
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use better_vdf::{BoolEncoding, ErrorKind, Whitespace};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Settings {
+    enabled: bool,
+    name: String,
+}
+
+#[test]
+fn defaults_are_strict() {
+    let options = better_vdf::options();
+
+    let err = options
+        .from_str::<Settings>("\"enabled\" \"true\" \"name\" \"x\"")
+        .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::ExpectedBoolean(_)));
+
+    let err = options
+        .from_str::<Settings>("\"enabled\" \"1\" \"name\" \"x\" }")
+        .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::TrailingCharacters));
+
+    assert!(options
+        .from_str::<Settings>("{ \"enabled\" \"1\" \"name\" \"x\" }")
+        .is_err());
+    assert!(options
+        .from_str::<Settings>("\"enabled\"\u{a0}\"1\" \"name\" \"x\"")
+        .is_err());
+}
+
+#[test]
+fn bools() {
+    let options = better_vdf::options().bools(BoolEncoding::Lenient);
+
+    for (text, expected) in [("1", true), ("TRUE", true), ("0", false), ("false", false)] {
+        let settings: Settings = options
+            .from_str(&format!("\"enabled\" \"{text}\" \"name\" \"x\""))
+            .unwrap();
+        assert_eq!(settings.enabled, expected);
+    }
+
+    let err = options
+        .from_str::<Settings>("\"enabled\" \"yes\" \"name\" \"x\"")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected boolean, found \"yes\" at line 1 column 11"
+    );
+}
+
+#[test]
+fn braced_root() {
+    let options = better_vdf::options().braced_root(true);
+
+    let braced: BTreeMap<String, String> = options.from_str("{ \"a\" \"1\" }").unwrap();
+    let unbraced: BTreeMap<String, String> = options.from_str("\"a\" \"1\"").unwrap();
+    assert_eq!(braced, unbraced);
+
+    let value: better_vdf::Value = options.from_str("{ \"a\" { \"b\" \"c\" } }").unwrap();
+    assert_eq!(value.get("a").and_then(|x| x.get("b")), Some(&"c".into()));
+
+    assert!(options
+        .from_str::<BTreeMap<String, String>>("{ \"a\" \"1\"")
+        .is_err());
+}
+
+#[test]
+fn whitespace_and_trailing_characters() {
+    let settings: Settings = better_vdf::options()
+        .whitespace(Whitespace::Unicode)
+        .trailing_characters(true)
+        .from_str("\"enabled\"\u{a0}\"1\"\u{2003}\"name\" \"x\" } junk")
+        .unwrap();
+
+    assert_eq!(
+        settings,
+        Settings {
+            enabled: true,
+            name: "x".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn lenient_profile_is_reusable() {
+    let options = better_vdf::options().lenient();
+
+    let first: Settings = options
+        .from_str("{ /* note */ \"enabled\" \"True\" \"name\" \"a\" }")
+        .unwrap();
+    let second: Settings = options
+        .from_slice(b"\xef\xbb\xbf\"enabled\" \"0\" \"name\" \"b\"")
+        .unwrap();
+
+    assert!(first.enabled);
+    assert!(!second.enabled);
+}
+
+#[test]
+fn empty_strings_as_none() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Profile {
+        name: Option<String>,
+        level: Option<u32>,
+        missing: Option<String>,
+        block: Option<BTreeMap<String, String>>,
+    }
+
+    let text = r#""name" "" "level" "" "block" { }"#;
+
+    assert!(better_vdf::from_str::<Profile>(text).is_err());
+
+    let profile: Profile = better_vdf::options()
+        .empty_as_none(true)
+        .from_str(text)
+        .unwrap();
+    assert_eq!(
+        profile,
+        Profile {
+            name: None,
+            level: None,
+            missing: None,
+            block: Some(BTreeMap::new()),
+        }
+    );
+
+    let profile: Profile = better_vdf::options()
+        .empty_as_none(true)
+        .from_str(r#""name" "x" "level" "3""#)
+        .unwrap();
+    assert_eq!(profile.name.as_deref(), Some("x"));
+    assert_eq!(profile.level, Some(3));
+}
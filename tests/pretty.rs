@@ -0,0 +1,92 @@
+use better_vdf::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Manifest {
+    appid: u32,
+    name: String,
+    depots: Depots,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Depots {
+    size: u64,
+    tags: Vec<String>,
+}
+
+fn manifest() -> Manifest {
+    Manifest {
+        appid: 440,
+        name: "Team Fortress 2".to_owned(),
+        depots: Depots {
+            size: 1024,
+            tags: vec!["a".to_owned()],
+        },
+    }
+}
+
+fn pretty(config: PrettyConfig) -> String {
+    let text = better_vdf::to_string_pretty(&manifest(), &config).unwrap();
+    let back: Manifest =
+        better_vdf::from_str(&text).unwrap_or_else(|err| panic!("{err} in\n{text}"));
+    assert_eq!(back, manifest());
+    text
+}
+
+#[test]
+fn default_matches_to_string() {
+    assert_eq!(
+        pretty(PrettyConfig::new()),
+        better_vdf::to_string(&manifest()).unwrap()
+    );
+}
+
+#[test]
+fn steam_layout() {
+    let config = PrettyConfig::new()
+        .braces_on_own_line(true)
+        .newline("\r\n")
+        .final_newline(false);
+
+    assert_eq!(
+        pretty(config),
+        "\"appid\"\t\t\"440\"\r\n\
+         \"name\"\t\t\"Team Fortress 2\"\r\n\
+         \"depots\"\r\n\
+         {\r\n\
+         \t\"size\"\t\t\"1024\"\r\n\
+         \t\"tags\"\r\n\
+         \t{\r\n\
+         \t\t\"0\"\t\t\"a\"\r\n\
+         \t}\r\n\
+         }"
+    );
+}
+
+#[test]
+fn indent_separator_and_alignment() {
+    let config = PrettyConfig::new()
+        .indent("  ")
+        .separator(" ")
+        .align_values(8);
+
+    assert_eq!(
+        pretty(config),
+        "\"appid\" \"440\"\n\
+         \"name\"  \"Team Fortress 2\"\n\
+         \"depots\" {\n  \
+           \"size\"  \"1024\"\n  \
+           \"tags\"  {\n    \
+             \"0\"     \"a\"\n  \
+           }\n\
+         }\n"
+    );
+}
+
+#[test]
+fn compact() {
+    assert_eq!(
+        pretty(PrettyConfig::compact()),
+        r#""appid" "440" "name" "Team Fortress 2" "depots" { "size" "1024" "tags" { "0" "a" } }"#
+    );
+}
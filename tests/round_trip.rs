@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, HashMap};
+
+use better_vdf::{ErrorKind, Object, Value};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+fn round_trip<T>(value: &T) -> T
+where
+    T: Serialize + DeserializeOwned,
+{
+    let text = better_vdf::to_string(value).unwrap();
+    better_vdf::from_str(&text).unwrap_or_else(|err| panic!("{err} in\n{text}"))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Test {
+    test: TestData,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct TestData {
+    name: String,
+    list: Vec<TestObj>,
+    map: BTreeMap<u64, i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct TestObj {
+    obj: String,
+    id: usize,
+    weight: f32,
+}
+
+#[test]
+fn readme_example() {
+    let test = Test {
+        test: TestData {
+            name: "Better VDF".to_owned(),
+            list: vec![
+                TestObj {
+                    obj: "main_obj".to_owned(),
+                    id: 19231,
+                    weight: 12.9,
+                },
+                TestObj {
+                    obj: "secondary_obj".to_owned(),
+                    id: 381928,
+                    weight: 5.12,
+                },
+            ],
+            map: BTreeMap::from([(228980, 12318293), (278319, -12393180)]),
+        },
+    };
+
+    assert_eq!(round_trip(&test), test);
+}
+
+#[test]
+fn root_is_unbraced() {
+    let text = better_vdf::to_string(&BTreeMap::from([("a", "1"), ("b", "2")])).unwrap();
+
+    assert_eq!(text, "\"a\"\t\t\"1\"\n\"b\"\t\t\"2\"\n");
+}
+
+#[test]
+fn nested_structs() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Outer {
+        inner: Inner,
+        enabled: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Inner {
+        deeper: Deeper,
+        text: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Deeper {
+        value: i32,
+    }
+
+    let outer = Outer {
+        inner: Inner {
+            deeper: Deeper { value: -3 },
+            text: "quote \" and \\ backslash\tand tab".to_owned(),
+        },
+        enabled: true,
+    };
+
+    assert_eq!(round_trip(&outer), outer);
+}
+
+#[test]
+fn maps() {
+    let map = HashMap::from([
+        ("first".to_owned(), HashMap::from([("x".to_owned(), 1u8)])),
+        ("second".to_owned(), HashMap::new()),
+    ]);
+
+    assert_eq!(round_trip(&map), map);
+}
+
+#[test]
+fn map_keys() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Slot {
+        Primary,
+        Secondary,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct AppId(u32);
+
+    let slots = BTreeMap::from([(Slot::Primary, 1u8), (Slot::Secondary, 2)]);
+    assert_eq!(
+        better_vdf::to_string(&slots).unwrap(),
+        "\"Primary\"\t\t\"1\"\n\"Secondary\"\t\t\"2\"\n"
+    );
+    assert_eq!(round_trip(&slots), slots);
+
+    let apps = BTreeMap::from([(AppId(440), "tf"), (AppId(570), "dota")]);
+    assert_eq!(
+        better_vdf::to_string(&apps).unwrap(),
+        "\"440\"\t\t\"tf\"\n\"570\"\t\t\"dota\"\n"
+    );
+
+    let flags = BTreeMap::from([(true, "yes".to_owned()), (false, "no".to_owned())]);
+    assert_eq!(round_trip(&flags), flags);
+
+    let letters = BTreeMap::from([('a', "first")]);
+    assert_eq!(
+        better_vdf::to_string(&letters).unwrap(),
+        "\"a\"\t\t\"first\"\n"
+    );
+
+    let negative = BTreeMap::from([(-1i64, "a")]);
+    assert_eq!(
+        better_vdf::to_string(&negative).unwrap(),
+        "\"-1\"\t\t\"a\"\n"
+    );
+}
+
+#[test]
+fn keys_must_be_strings() {
+    fn fails<K: Serialize + Ord>(key: K) {
+        let err = better_vdf::to_string(&BTreeMap::from([(key, "value")])).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::KeyMustBeAString), "{err}");
+        assert_eq!(err.to_string(), "key must be a string");
+    }
+
+    fails((1, 2));
+    fails(vec!["a"]);
+    fails(Some("a"));
+    fails(());
+    fails(BTreeMap::from([("a", "b")]));
+}
+
+#[test]
+fn sequences() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Lists {
+        numbers: Vec<u32>,
+        nested: Vec<Vec<String>>,
+        pair: (String, i64),
+        empty: Vec<u8>,
+    }
+
+    let lists = Lists {
+        numbers: (0..12).collect(),
+        nested: vec![vec!["a".to_owned(), "b".to_owned()], vec![]],
+        pair: ("left".to_owned(), -1),
+        empty: Vec::new(),
+    };
+
+    assert_eq!(round_trip(&lists), lists);
+}
+
+#[test]
+fn options() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Options {
+        present: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        absent: Option<String>,
+        block: Option<BTreeMap<String, u8>>,
+    }
+
+    let options = Options {
+        present: Some("here".to_owned()),
+        absent: None,
+        block: Some(BTreeMap::from([("k".to_owned(), 7)])),
+    };
+
+    assert_eq!(round_trip(&options), options);
+}
+
+#[test]
+fn none_fields_are_skipped() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Fields {
+        first: Option<String>,
+        block: Option<BTreeMap<String, u8>>,
+        last: Option<u8>,
+    }
+
+    let fields = Fields {
+        first: None,
+        block: None,
+        last: Some(1),
+    };
+
+    assert_eq!(
+        better_vdf::to_string(&fields).unwrap(),
+        "\"last\"\t\t\"1\"\n"
+    );
+    assert_eq!(round_trip(&fields), fields);
+}
+
+#[test]
+fn none_elements_are_skipped() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct List {
+        list: Vec<Option<u8>>,
+    }
+
+    let list = List {
+        list: vec![Some(1), None, Some(3), None],
+    };
+
+    let text = better_vdf::to_string(&list).unwrap();
+    assert_eq!(
+        text,
+        "\"list\"\t\t{\n\t\"0\"\t\t\"1\"\n\t\"1\"\t\t\"3\"\n}\n"
+    );
+    assert_eq!(
+        better_vdf::from_str::<List>(&text).unwrap().list,
+        [Some(1), Some(3)]
+    );
+}
+
+#[test]
+fn empty_roots() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Optional {
+        a: Option<String>,
+    }
+
+    let map = BTreeMap::<String, String>::new();
+    assert_eq!(better_vdf::to_string(&map).unwrap(), "");
+    assert_eq!(round_trip(&map), map);
+
+    let optional = Optional { a: None };
+    assert_eq!(round_trip(&optional), optional);
+
+    for text in ["  \n", "// nothing here\n", "\"a\" \"b\" [$NEVER]"] {
+        let map: BTreeMap<String, String> = better_vdf::from_str(text).unwrap();
+        assert!(map.is_empty(), "{text:?}");
+    }
+}
+
+#[test]
+fn values() {
+    let value: Value =
+        better_vdf::from_str(r#""root" { "a" "1" "a" "2" "block" { "nested" "x" } } "other" "y""#)
+            .unwrap();
+
+    assert_eq!(round_trip(&value), value);
+
+    let object: Object = value.as_object().unwrap().clone();
+    assert_eq!(round_trip(&object), object);
+}
+
+#[test]
+fn values_keep_numeric_text() {
+    let text = "\"a\"\t\t\"1e5\"\n\"b\"\t\t\"007\"\n\"c\"\t\t\"1.000000\"\n";
+    let value: Value = better_vdf::from_str(text).unwrap();
+
+    assert_eq!(value["a"], Value::String("1e5".to_owned()));
+    assert_eq!(better_vdf::to_string(&value).unwrap(), text);
+}
+
+#[test]
+fn named_root() {
+    let data = TestObj {
+        obj: "main_obj".to_owned(),
+        id: 19231,
+        weight: 12.5,
+    };
+
+    let text = better_vdf::to_string_with_root("AppState", &data).unwrap();
+    let (root, back) = better_vdf::from_str_with_root::<TestObj>(&text).unwrap();
+
+    assert_eq!(root, "AppState");
+    assert_eq!(back, data);
+    assert!(better_vdf::from_str_with_root::<TestObj>(&format!("{text}{text}")).is_err());
+    assert!(better_vdf::from_str_with_root::<TestObj>("").is_err());
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Mode {
+    Casual,
+    Ranked(u32),
+    Custom(String, i8),
+    Workshop { id: u64, name: String },
+}
+
+#[test]
+fn externally_tagged_enums() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Modes {
+        modes: Vec<Mode>,
+        default: Mode,
+    }
+
+    let modes = Modes {
+        modes: vec![
+            Mode::Casual,
+            Mode::Ranked(3),
+            Mode::Custom("1v1".to_owned(), -2),
+            Mode::Workshop {
+                id: 7,
+                name: "Arena".to_owned(),
+            },
+        ],
+        default: Mode::Ranked(1),
+    };
+
+    assert_eq!(round_trip(&modes), modes);
+    assert_eq!(
+        round_trip(&Mode::Workshop {
+            id: 1,
+            name: "x".to_owned()
+        }),
+        Mode::Workshop {
+            id: 1,
+            name: "x".to_owned()
+        }
+    );
+
+    assert_eq!(round_trip(&Mode::Casual), Mode::Casual);
+
+    let text = better_vdf::to_string(&BTreeMap::from([("mode", Mode::Casual)])).unwrap();
+    assert_eq!(text, "\"mode\"\t\t\"Casual\"\n");
+}
+
+#[test]
+fn internally_tagged_enums() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(tag = "type")]
+    enum Item {
+        Weapon {
+            #[serde(with = "better_vdf::number")]
+            damage: u32,
+            #[serde(with = "better_vdf::number")]
+            speed: f64,
+        },
+        Hat {
+            name: String,
+        },
+        Empty,
+    }
+
+    let items = BTreeMap::from([
+        (
+            "0".to_owned(),
+            Item::Weapon {
+                damage: 30,
+                speed: 1.25,
+            },
+        ),
+        (
+            "1".to_owned(),
+            Item::Hat {
+                name: "Top Hat".to_owned(),
+            },
+        ),
+        ("2".to_owned(), Item::Empty),
+    ]);
+
+    assert_eq!(round_trip(&items), items);
+}
+
+#[test]
+fn untagged_enums() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Limit {
+        Count(#[serde(with = "better_vdf::number")] u32),
+        Name(String),
+    }
+
+    let limits = BTreeMap::from([
+        ("a".to_owned(), Limit::Count(5)),
+        ("b".to_owned(), Limit::Name("none".to_owned())),
+    ]);
+
+    assert_eq!(round_trip(&limits), limits);
+}
+
+#[test]
+fn flattened_maps_keep_numeric_strings() {
+    #[derive(Deserialize, Debug)]
+    struct AppState {
+        name: String,
+        #[serde(flatten)]
+        extra: HashMap<String, String>,
+    }
+
+    let state: AppState =
+        better_vdf::from_str(r#""appid" "440" "name" "Team Fortress 2" "LastOwner" "-1""#).unwrap();
+
+    assert_eq!(state.name, "Team Fortress 2");
+    assert_eq!(state.extra["appid"], "440");
+    assert_eq!(state.extra["LastOwner"], "-1");
+}
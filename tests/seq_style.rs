@@ -0,0 +1,130 @@
+use better_vdf::{Deserializer, SeqStyle, Serializer};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+fn to_string<T>(value: &T, style: SeqStyle) -> String
+where
+    T: Serialize,
+{
+    let mut output = Vec::new();
+    value
+        .serialize(&mut Serializer::new(&mut output).seq_style(style))
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+fn from_str<T>(input: &str, style: SeqStyle) -> T
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(&mut Deserializer::from_str(input).seq_style(style))
+        .unwrap_or_else(|err| panic!("{err} in\n{input}"))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Inventory {
+    item: Vec<Item>,
+    tags: Vec<String>,
+    grid: Vec<Vec<u8>>,
+    #[serde(with = "better_vdf::indexed")]
+    order: Vec<u32>,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Item {
+    id: u32,
+    slots: Vec<String>,
+}
+
+fn inventory() -> Inventory {
+    Inventory {
+        item: vec![
+            Item {
+                id: 1,
+                slots: vec!["head".to_owned()],
+            },
+            Item {
+                id: 2,
+                slots: vec!["hand".to_owned(), "back".to_owned()],
+            },
+        ],
+        tags: vec!["single".to_owned()],
+        grid: vec![vec![1, 2], vec![3]],
+        order: vec![2, 1],
+        name: "bag".to_owned(),
+    }
+}
+
+#[test]
+fn repeated_style_round_trips() {
+    let inventory = inventory();
+    let text = to_string(&inventory, SeqStyle::Repeated);
+
+    assert!(
+        text.starts_with("\"item\"\t\t{\n\t\"id\"\t\t\"1\"\n\t\"slots\"\t\t\"head\"\n}\n\"item\"")
+    );
+    assert!(text.contains("\"grid\"\t\t{\n\t\"0\"\t\t\"1\"\n\t\"1\"\t\t\"2\"\n}\n\"grid\"\t\t{"));
+    assert!(text.contains("\"order\"\t\t{\n\t\"0\"\t\t\"2\"\n"));
+    assert_eq!(from_str::<Inventory>(&text, SeqStyle::Repeated), inventory);
+}
+
+#[test]
+fn indexed_style_round_trips() {
+    let inventory = inventory();
+    let text = to_string(&inventory, SeqStyle::Indexed);
+
+    assert!(text.starts_with("\"item\"\t\t{\n\t\"0\"\t\t{\n"));
+    assert_eq!(from_str::<Inventory>(&text, SeqStyle::Indexed), inventory);
+}
+
+#[test]
+fn repeated_style_reads_interleaved_keys() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Hud {
+        #[serde(default)]
+        control: Vec<String>,
+        name: String,
+    }
+
+    let hud: Hud = from_str(
+        r#""control" "a" "name" "hud" "control" "b""#,
+        SeqStyle::Repeated,
+    );
+    assert_eq!(hud.control, ["a", "b"]);
+
+    let hud: Hud = from_str(r#""name" "hud""#, SeqStyle::Repeated);
+    assert!(hud.control.is_empty());
+}
+
+#[test]
+fn repeated_style_in_variants() {
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Mode {
+        List(Vec<String>),
+        Casual,
+    }
+
+    let modes = BTreeMap::from([
+        (
+            "m".to_owned(),
+            Mode::List(vec!["a".to_owned(), "b".to_owned()]),
+        ),
+        ("n".to_owned(), Mode::Casual),
+    ]);
+    let text = to_string(&modes, SeqStyle::Repeated);
+
+    assert_eq!(
+        text,
+        "\"m\"\t\t{\n\t\"List\"\t\t\"a\"\n\t\"List\"\t\t\"b\"\n}\n\"n\"\t\t\"Casual\"\n"
+    );
+    assert_eq!(
+        from_str::<BTreeMap<String, Mode>>(&text, SeqStyle::Repeated),
+        modes
+    );
+
+    let list = Mode::List(vec!["a".to_owned(), "b".to_owned()]);
+    let text = to_string(&list, SeqStyle::Repeated);
+    assert_eq!(from_str::<Mode>(&text, SeqStyle::Repeated), list);
+}
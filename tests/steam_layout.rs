@@ -0,0 +1,84 @@
+use better_vdf::{PrettyConfig, Value};
+use serde::Serialize;
+
+// The golden files are written by hand in the layout Steam uses, with made-up paths and ids.
+// They are not captures of real files, so `goldens_use_steam_separators` checks the layout of
+// the files themselves, and they must not be regenerated from the writer's output.
+
+/// Reads a file in the layout Steam writes and checks that writing it back changes nothing.
+fn rewrite(golden: &str) {
+    let value: Value = better_vdf::from_str(golden).unwrap();
+    let text = better_vdf::to_string_pretty(&value, &PrettyConfig::steam()).unwrap();
+
+    assert_eq!(text, golden);
+}
+
+#[test]
+fn libraryfolders() {
+    rewrite(include_str!("golden/libraryfolders.vdf"));
+}
+
+#[test]
+fn appmanifest() {
+    rewrite(include_str!("golden/appmanifest_440.acf"));
+}
+
+#[test]
+fn config() {
+    rewrite(include_str!("golden/config.vdf"));
+}
+
+#[test]
+fn goldens_use_steam_separators() {
+    let goldens = [
+        include_str!("golden/libraryfolders.vdf"),
+        include_str!("golden/appmanifest_440.acf"),
+        include_str!("golden/config.vdf"),
+    ];
+
+    for line in goldens.iter().flat_map(|x| x.lines()) {
+        let line = line.trim_start_matches('\t');
+        assert!(
+            matches!(line, "{" | "}")
+                || !line.contains('\t') && line.matches('"').count() == 2
+                || line.contains("\"\t\t\"") && !line.contains("\t\t\t"),
+            "{line:?}"
+        );
+    }
+}
+
+#[test]
+fn structs() {
+    #[derive(Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Depot {
+        manifest: String,
+        size: u64,
+        tags: Vec<&'static str>,
+    }
+
+    let depot = Depot {
+        manifest: "2174530447174253342".to_owned(),
+        size: 12871442211,
+        tags: vec!["a", "b"],
+    };
+
+    let mut output = Vec::new();
+    depot
+        .serialize(&mut better_vdf::Serializer::pretty(
+            &mut output,
+            PrettyConfig::steam(),
+        ))
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\"Manifest\"\t\t\"2174530447174253342\"\n\
+         \"Size\"\t\t\"12871442211\"\n\
+         \"Tags\"\n\
+         {\n\
+         \t\"0\"\t\t\"a\"\n\
+         \t\"1\"\t\t\"b\"\n\
+         }\n"
+    );
+}
@@ -0,0 +1,110 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use better_vdf::{Conditional, ErrorKind};
+use serde::Deserialize;
+
+type Entries = BTreeMap<String, String>;
+
+fn read(text: &str) -> Entries {
+    better_vdf::from_str(text).unwrap_or_else(|err| panic!("{err} in {text}"))
+}
+
+#[test]
+fn escape_sequences() {
+    let entries =
+        read(r#""newline" "a\nb" "tab" "a\tb" "backslash" "C:\\Steam\\" "quote" "say \"hi\"""#);
+
+    assert_eq!(entries["newline"], "a\nb");
+    assert_eq!(entries["tab"], "a\tb");
+    assert_eq!(entries["backslash"], "C:\\Steam\\");
+    assert_eq!(entries["quote"], "say \"hi\"");
+}
+
+#[test]
+fn unknown_escapes_are_kept() {
+    let entries = read(r#""path" "materials\models\player""#);
+    assert_eq!(entries["path"], r"materials\models\player");
+}
+
+#[test]
+fn escaped_keys() {
+    let entries = read(r#""a \"b\"" "1""#);
+    assert_eq!(entries["a \"b\""], "1");
+}
+
+#[test]
+fn strings_borrow_unless_escaped() {
+    #[derive(Deserialize)]
+    struct Strings<'a> {
+        #[serde(borrow)]
+        plain: Cow<'a, str>,
+        #[serde(borrow)]
+        escaped: Cow<'a, str>,
+    }
+
+    let strings: Strings = better_vdf::from_str(r#""plain" "abc" "escaped" "a\"b""#).unwrap();
+    assert!(matches!(strings.plain, Cow::Borrowed("abc")));
+    assert!(matches!(strings.escaped, Cow::Owned(ref s) if s == "a\"b"));
+}
+
+#[test]
+fn escapes_round_trip() {
+    let entries = Entries::from([
+        (
+            "launch".to_owned(),
+            "-novid +exec \"autoexec.cfg\"".to_owned(),
+        ),
+        ("path".to_owned(), "C:\\Program Files\\".to_owned()),
+        ("lines".to_owned(), "one\ntwo\tthree".to_owned()),
+    ]);
+
+    let text = better_vdf::to_string(&entries).unwrap();
+    assert_eq!(
+        text,
+        "\"launch\"\t\t\"-novid +exec \\\"autoexec.cfg\\\"\"\n\
+         \"lines\"\t\t\"one\\ntwo\\tthree\"\n\
+         \"path\"\t\t\"C:\\\\Program Files\\\\\"\n"
+    );
+    assert_eq!(read(&text), entries);
+}
+
+#[test]
+fn unquoted_tokens() {
+    let entries = read("LayoutVersion 4\n$basetexture concrete/wall01\n\"quoted\" bare");
+
+    assert_eq!(entries["LayoutVersion"], "4");
+    assert_eq!(entries["$basetexture"], "concrete/wall01");
+    assert_eq!(entries["quoted"], "bare");
+}
+
+#[test]
+fn unquoted_tokens_end_at_delimiters() {
+    #[derive(Deserialize, Debug)]
+    struct Doc {
+        block: Entries,
+        quote: String,
+        next: String,
+    }
+
+    let doc: Doc = better_vdf::from_str("block{inner value}quote\"quoted\"next last").unwrap();
+    assert_eq!(doc.block["inner"], "value");
+    assert_eq!(doc.quote, "quoted");
+    assert_eq!(doc.next, "last");
+
+    let entries = read("key value[$WIN32]");
+    assert!(entries.is_empty());
+
+    let entries: BTreeMap<String, Conditional<String>> = better_vdf::options()
+        .preserve_conditionals(true)
+        .from_str("key value[$WIN32] other \"x\"[!$X360]")
+        .unwrap();
+    assert_eq!(entries["key"].value, "value");
+    assert_eq!(entries["key"].condition.as_deref(), Some("$WIN32"));
+    assert_eq!(entries["other"].condition.as_deref(), Some("!$X360"));
+}
+
+#[test]
+fn conditions_without_values_fail() {
+    let err = better_vdf::from_str::<Entries>("key [$WIN32]").unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Eof), "{err}");
+}
@@ -0,0 +1,78 @@
+use std::{collections::BTreeMap, io};
+
+use better_vdf::{ErrorKind, PrettyConfig};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Inventory {
+    owner: String,
+    items: Vec<BTreeMap<String, u32>>,
+}
+
+fn inventory() -> Inventory {
+    Inventory {
+        owner: "76561198000000000".to_owned(),
+        items: (0..100)
+            .map(|id| BTreeMap::from([("id".to_owned(), id), ("level".to_owned(), id % 10)]))
+            .collect(),
+    }
+}
+
+#[test]
+fn writers_match_strings() {
+    let value = inventory();
+    let text = better_vdf::to_string(&value).unwrap();
+
+    let mut output = Vec::new();
+    better_vdf::to_writer(&mut output, &value).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), text);
+
+    assert_eq!(better_vdf::to_vec(&value).unwrap(), text.as_bytes());
+
+    for config in [
+        PrettyConfig::new(),
+        PrettyConfig::compact(),
+        PrettyConfig::steam(),
+    ] {
+        let mut output = Vec::new();
+        better_vdf::to_writer_pretty(&mut output, &value, &config).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            better_vdf::to_string_pretty(&value, &config).unwrap()
+        );
+    }
+}
+
+/// Accepts a few bytes and then fails.
+struct FailingWriter {
+    remaining: usize,
+}
+
+impl io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "disk full"));
+        }
+
+        let len = buf.len().min(self.remaining);
+        self.remaining -= len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn writer_errors() {
+    for remaining in [0, 10, 100] {
+        let err = better_vdf::to_writer(FailingWriter { remaining }, &inventory()).unwrap_err();
+
+        match err.kind() {
+            ErrorKind::Io(err) => assert_eq!(err.kind(), io::ErrorKind::BrokenPipe),
+            kind => panic!("{kind}"),
+        }
+        assert_eq!(err.position(), None);
+    }
+}